@@ -8,7 +8,7 @@
 //!
 //! To run this example:
 //! ```
-//! QRZ_API_KEY=your-api-key cargo run --example basic_usage
+//! QRZ_API_KEY=ABCD-1234-EF56-7890 cargo run --example basic_usage
 //! ```
 
 use chrono::{NaiveDate, NaiveTime};
@@ -1,77 +1,192 @@
-use crate::{error::QrzLogbookError, models::QsoRecord, QrzLogbookResult};
-use chrono::{NaiveDate, NaiveTime};
+use crate::{
+    error::QrzLogbookError,
+    models::{PropMode, QsoRecord},
+    QrzLogbookResult,
+};
+use chrono::{NaiveDate, NaiveTime, Timelike};
 use std::collections::HashMap;
 
 /// ADIF parser and formatter
 pub struct AdifParser;
 
+/// Precision of `time_on` and `time_off` in generated ADIF
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimePrecision {
+    /// HHMMSS when the time has seconds, otherwise HHMM
+    #[default]
+    Auto,
+    /// Always HHMM, dropping seconds
+    Minutes,
+    /// Always HHMMSS
+    Seconds,
+}
+
 impl AdifParser {
     /// Convert QSO record to ADIF format
     pub fn to_adif(qso: &QsoRecord) -> String {
+        Self::to_adif_with_precision(qso, TimePrecision::Auto)
+    }
+
+    /// Convert QSO record to ADIF format with the given time precision
+    pub fn to_adif_with_precision(qso: &QsoRecord, precision: TimePrecision) -> String {
+        let mut adif = String::new();
+
+        for (name, value) in Self::fields_with_precision(qso, precision) {
+            adif.push_str(&format!("<{}:{}>{}", name, value.len(), value));
+        }
+
+        // End of record marker
+        adif.push_str("<eor>");
+
+        adif
+    }
+
+    /// Convert QSO record to ADIF field names and values
+    ///
+    /// Field names are lowercase and values use ADIF formats; built-in fields
+    /// come first, followed by the additional fields.
+    pub fn to_fields(qso: &QsoRecord) -> Vec<(String, String)> {
+        Self::fields_with_precision(qso, TimePrecision::Auto)
+    }
+
+    fn fields_with_precision(qso: &QsoRecord, precision: TimePrecision) -> Vec<(String, String)> {
         let mut fields = Vec::new();
+        let mut push = |name: &str, value: String| fields.push((name.to_string(), value));
 
         // Required fields
-        fields.push(format!("<call:{}>{}", qso.call.len(), qso.call));
-        fields.push(format!(
-            "<station_callsign:{}>{}",
-            qso.station_callsign.len(),
-            qso.station_callsign
-        ));
-        fields.push(format!("<qso_date:8>{}", qso.qso_date.format("%Y%m%d")));
-        fields.push(format!(
-            "<time_on:{}>{}",
-            format_time(&qso.time_on).len(),
-            format_time(&qso.time_on)
-        ));
-        fields.push(format!("<band:{}>{}", qso.band.len(), qso.band));
-        fields.push(format!("<mode:{}>{}", qso.mode.len(), qso.mode));
+        push("call", qso.call.clone());
+        push("station_callsign", qso.station_callsign.clone());
+        push("qso_date", qso.qso_date.format("%Y%m%d").to_string());
+        push("time_on", format_time(&qso.time_on, precision));
+        push("band", qso.band.clone());
+        push("mode", qso.mode.clone());
 
         // Optional fields
         if let Some(ref time_off) = qso.time_off {
-            let time_str = format_time(time_off);
-            fields.push(format!("<time_off:{}>{}", time_str.len(), time_str));
+            push("time_off", format_time(time_off, precision));
+        }
+
+        // Written when set or when the QSO ends after midnight
+        if let Some(date_off) = qso.qso_date_off {
+            push("qso_date_off", date_off.format("%Y%m%d").to_string());
+        } else if let Some(end) = qso.end_utc() {
+            if end.date_naive() != qso.qso_date {
+                push("qso_date_off", end.format("%Y%m%d").to_string());
+            }
         }
 
         if let Some(freq) = qso.freq {
-            let freq_str = freq.to_string();
-            fields.push(format!("<freq:{}>{}", freq_str.len(), freq_str));
+            push("freq", freq.to_string());
         }
 
         if let Some(ref rst) = qso.rst_sent {
-            fields.push(format!("<rst_sent:{}>{}", rst.len(), rst));
+            push("rst_sent", rst.clone());
         }
 
         if let Some(ref rst) = qso.rst_rcvd {
-            fields.push(format!("<rst_rcvd:{}>{}", rst.len(), rst));
+            push("rst_rcvd", rst.clone());
         }
 
         if let Some(ref qth) = qso.qth {
-            fields.push(format!("<qth:{}>{}", qth.len(), qth));
+            push("qth", qth.clone());
         }
 
         if let Some(ref name) = qso.name {
-            fields.push(format!("<name:{}>{}", name.len(), name));
+            push("name", name.clone());
         }
 
         if let Some(ref comment) = qso.comment {
-            fields.push(format!("<comment:{}>{}", comment.len(), comment));
+            push("comment", comment.clone());
         }
 
-        // Additional fields
-        for (key, value) in &qso.additional_fields {
-            fields.push(format!("<{}:{}>{}", key.to_lowercase(), value.len(), value));
+        let strings = [
+            ("gridsquare", &qso.gridsquare),
+            ("my_gridsquare", &qso.my_gridsquare),
+            ("operator", &qso.operator),
+            ("contest_id", &qso.contest_id),
+            ("srx_string", &qso.srx_string),
+            ("stx_string", &qso.stx_string),
+            ("state", &qso.state),
+            ("cnty", &qso.cnty),
+            ("sat_name", &qso.sat_name),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                push(name, value.clone());
+            }
         }
 
-        // End of record marker
-        fields.push("<eor>".to_string());
+        let numbers = [
+            ("srx", qso.srx),
+            ("stx", qso.stx),
+            ("dxcc", qso.dxcc),
+            ("cqz", qso.cqz),
+            ("ituz", qso.ituz),
+        ];
+        for (name, value) in numbers {
+            if let Some(value) = value {
+                push(name, value.to_string());
+            }
+        }
 
-        fields.join("")
+        if let Some(tx_pwr) = qso.tx_pwr {
+            push("tx_pwr", tx_pwr.to_string());
+        }
+
+        if let Some(ref prop_mode) = qso.prop_mode {
+            push("prop_mode", prop_mode.to_string());
+        }
+
+        let statuses = [
+            ("qsl_sent", qso.qsl_sent),
+            ("qsl_rcvd", qso.qsl_rcvd),
+            ("lotw_qsl_sent", qso.lotw_qsl_sent),
+            ("lotw_qsl_rcvd", qso.lotw_qsl_rcvd),
+        ];
+        for (name, value) in statuses {
+            if let Some(value) = value {
+                push(name, value.to_string());
+            }
+        }
+
+        if let Some(date) = qso.qslsdate {
+            push("qslsdate", date.format("%Y%m%d").to_string());
+        }
+
+        if let Some(date) = qso.qslrdate {
+            push("qslrdate", date.format("%Y%m%d").to_string());
+        }
+
+        // Additional fields, unless a typed field already covers them
+        for (key, value) in &qso.additional_fields {
+            let key = key.to_lowercase();
+            if !fields.iter().any(|(name, _)| *name == key) {
+                fields.push((key, value.clone()));
+            }
+        }
+
+        fields
     }
 
     /// Parse ADIF string into QSO records
+    ///
+    /// An ADIF header terminated by `<eoh>` is skipped, and the `<eoh>` and
+    /// `<eor>` markers are matched case-insensitively.
     pub fn parse_adif(adif: &str) -> QrzLogbookResult<Vec<QsoRecord>> {
         let mut qsos = Vec::new();
-        let records = adif.split("<eor>");
+
+        // ASCII lowercasing keeps byte offsets, so markers found in the
+        // lowercase copy can be used to slice the original string
+        let lower = adif.to_ascii_lowercase();
+        let body_start = lower.find("<eoh>").map(|pos| pos + 5).unwrap_or(0);
+
+        let mut records = Vec::new();
+        let mut start = body_start;
+        while let Some(offset) = lower[start..].find("<eor>") {
+            records.push(&adif[start..start + offset]);
+            start += offset + 5;
+        }
+        records.push(&adif[start..]);
 
         for record in records {
             let record = record.trim();
@@ -150,7 +265,8 @@ impl AdifParser {
         Self::fields_to_qso(fields)
     }
 
-    fn fields_to_qso(fields: HashMap<String, String>) -> QrzLogbookResult<QsoRecord> {
+    /// Build a QSO record from lowercase ADIF field names and their values
+    pub(crate) fn fields_to_qso(fields: HashMap<String, String>) -> QrzLogbookResult<QsoRecord> {
         let mut additional_fields = fields.clone();
 
         // Extract required fields
@@ -184,6 +300,10 @@ impl AdifParser {
             .remove("time_off")
             .map(|s| parse_time(&s))
             .transpose()?;
+        let qso_date_off = additional_fields
+            .remove("qso_date_off")
+            .map(|s| parse_date(&s))
+            .transpose()?;
 
         let freq = additional_fields
             .remove("freq")
@@ -197,12 +317,45 @@ impl AdifParser {
         let name = additional_fields.remove("name");
         let comment = additional_fields.remove("comment");
 
+        let gridsquare = additional_fields.remove("gridsquare");
+        let my_gridsquare = additional_fields.remove("my_gridsquare");
+        let operator = additional_fields.remove("operator");
+        let contest_id = additional_fields.remove("contest_id");
+        let srx_string = additional_fields.remove("srx_string");
+        let stx_string = additional_fields.remove("stx_string");
+        let state = additional_fields.remove("state");
+        let cnty = additional_fields.remove("cnty");
+        let sat_name = additional_fields.remove("sat_name");
+
+        // Typed optional fields: empty values are unset, and values that
+        // don't parse stay in additional_fields instead of failing the record
+        let tx_pwr = take_lenient(&mut additional_fields, "tx_pwr", |s| s.parse().ok());
+        let srx = take_lenient(&mut additional_fields, "srx", |s| s.parse().ok());
+        let stx = take_lenient(&mut additional_fields, "stx", |s| s.parse().ok());
+        let dxcc = take_lenient(&mut additional_fields, "dxcc", |s| s.parse().ok());
+        let cqz = take_lenient(&mut additional_fields, "cqz", |s| s.parse().ok());
+        let ituz = take_lenient(&mut additional_fields, "ituz", |s| s.parse().ok());
+
+        let prop_mode = take_lenient(&mut additional_fields, "prop_mode", |s| {
+            Some(PropMode::from(s))
+        });
+
+        let qsl_sent = take_lenient(&mut additional_fields, "qsl_sent", |s| s.parse().ok());
+        let qsl_rcvd = take_lenient(&mut additional_fields, "qsl_rcvd", |s| s.parse().ok());
+        let lotw_qsl_sent =
+            take_lenient(&mut additional_fields, "lotw_qsl_sent", |s| s.parse().ok());
+        let lotw_qsl_rcvd =
+            take_lenient(&mut additional_fields, "lotw_qsl_rcvd", |s| s.parse().ok());
+        let qslsdate = take_lenient(&mut additional_fields, "qslsdate", |s| parse_date(s).ok());
+        let qslrdate = take_lenient(&mut additional_fields, "qslrdate", |s| parse_date(s).ok());
+
         Ok(QsoRecord {
             call,
             station_callsign,
             qso_date,
             time_on,
             time_off,
+            qso_date_off,
             band,
             mode,
             freq,
@@ -211,16 +364,69 @@ impl AdifParser {
             qth,
             name,
             comment,
+            gridsquare,
+            my_gridsquare,
+            tx_pwr,
+            operator,
+            contest_id,
+            srx,
+            stx,
+            srx_string,
+            stx_string,
+            state,
+            cnty,
+            dxcc,
+            cqz,
+            ituz,
+            prop_mode,
+            sat_name,
+            qsl_sent,
+            qsl_rcvd,
+            qslsdate,
+            qslrdate,
+            lotw_qsl_sent,
+            lotw_qsl_rcvd,
             additional_fields,
         })
     }
 }
 
-fn format_time(time: &NaiveTime) -> String {
-    time.format("%H%M").to_string()
+fn format_time(time: &NaiveTime, precision: TimePrecision) -> String {
+    let seconds = match precision {
+        TimePrecision::Auto => time.second() != 0,
+        TimePrecision::Minutes => false,
+        TimePrecision::Seconds => true,
+    };
+
+    if seconds {
+        time.format("%H%M%S").to_string()
+    } else {
+        time.format("%H%M").to_string()
+    }
+}
+
+/// Remove and parse an optional typed field
+///
+/// Empty values become `None`; values that fail to parse are left in
+/// `fields` so they survive as additional fields.
+fn take_lenient<T>(
+    fields: &mut HashMap<String, String>,
+    name: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Option<T> {
+    let value = fields.remove(name)?;
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let parsed = parse(trimmed);
+    if parsed.is_none() {
+        fields.insert(name.to_string(), value);
+    }
+    parsed
 }
 
-fn parse_date(date_str: &str) -> QrzLogbookResult<NaiveDate> {
+pub(crate) fn parse_date(date_str: &str) -> QrzLogbookResult<NaiveDate> {
     if date_str.len() != 8 {
         return Err(QrzLogbookError::adif_parse(
             "Date must be 8 characters (YYYYMMDD)",
@@ -241,7 +447,7 @@ fn parse_date(date_str: &str) -> QrzLogbookResult<NaiveDate> {
         .ok_or_else(|| QrzLogbookError::adif_parse("Invalid date"))
 }
 
-fn parse_time(time_str: &str) -> QrzLogbookResult<NaiveTime> {
+pub(crate) fn parse_time(time_str: &str) -> QrzLogbookResult<NaiveTime> {
     let time_str = if time_str.len() == 4 {
         format!("{}00", time_str) // Add seconds if not present
     } else {
@@ -268,9 +474,61 @@ fn parse_time(time_str: &str) -> QrzLogbookResult<NaiveTime> {
         .ok_or_else(|| QrzLogbookError::adif_parse("Invalid time"))
 }
 
+/// ADIF band edges in MHz
+const BANDS: &[(&str, f64, f64)] = &[
+    ("2190m", 0.1357, 0.1378),
+    ("630m", 0.472, 0.479),
+    ("560m", 0.501, 0.504),
+    ("160m", 1.8, 2.0),
+    ("80m", 3.5, 4.0),
+    ("60m", 5.06, 5.45),
+    ("40m", 7.0, 7.3),
+    ("30m", 10.1, 10.15),
+    ("20m", 14.0, 14.35),
+    ("17m", 18.068, 18.168),
+    ("15m", 21.0, 21.45),
+    ("12m", 24.89, 24.99),
+    ("10m", 28.0, 29.7),
+    ("8m", 40.0, 45.0),
+    ("6m", 50.0, 54.0),
+    ("4m", 70.0, 71.0),
+    ("2m", 144.0, 148.0),
+    ("1.25m", 222.0, 225.0),
+    ("70cm", 420.0, 450.0),
+    ("33cm", 902.0, 928.0),
+    ("23cm", 1240.0, 1300.0),
+    ("13cm", 2300.0, 2450.0),
+    ("9cm", 3300.0, 3500.0),
+    ("6cm", 5650.0, 5925.0),
+    ("3cm", 10000.0, 10500.0),
+    ("1.25cm", 24000.0, 24250.0),
+    ("6mm", 47000.0, 47200.0),
+    ("4mm", 75500.0, 81000.0),
+    ("2.5mm", 119980.0, 123000.0),
+    ("2mm", 134000.0, 149000.0),
+    ("1mm", 241000.0, 250000.0),
+];
+
+/// Lower and upper edge in MHz of an ADIF band (e.g. "20m")
+pub fn band_edges(band: &str) -> Option<(f64, f64)> {
+    BANDS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(band))
+        .map(|(_, lower, upper)| (*lower, *upper))
+}
+
+/// Look up the ADIF band (e.g. "20m") containing a frequency in MHz
+pub fn band_for_freq(freq_mhz: f64) -> Option<&'static str> {
+    BANDS
+        .iter()
+        .find(|(_, lower, upper)| freq_mhz >= *lower && freq_mhz <= *upper)
+        .map(|(band, _, _)| *band)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::QslStatus;
     use chrono::{NaiveDate, NaiveTime};
 
     #[test]
@@ -281,6 +539,7 @@ mod tests {
             qso_date: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
             time_on: NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
             time_off: None,
+            qso_date_off: None,
             band: "20m".to_string(),
             mode: "SSB".to_string(),
             freq: Some(14.200),
@@ -289,6 +548,28 @@ mod tests {
             qth: None,
             name: None,
             comment: None,
+            gridsquare: Some("FN42".to_string()),
+            my_gridsquare: None,
+            tx_pwr: None,
+            operator: None,
+            contest_id: None,
+            srx: None,
+            stx: None,
+            srx_string: None,
+            stx_string: None,
+            state: None,
+            cnty: None,
+            dxcc: None,
+            cqz: None,
+            ituz: None,
+            prop_mode: None,
+            sat_name: None,
+            qsl_sent: None,
+            qsl_rcvd: None,
+            qslsdate: None,
+            qslrdate: None,
+            lotw_qsl_sent: None,
+            lotw_qsl_rcvd: None,
             additional_fields: HashMap::new(),
         };
 
@@ -297,6 +578,7 @@ mod tests {
         assert!(adif.contains("<station_callsign:5>K1ABC"));
         assert!(adif.contains("<qso_date:8>20240115"));
         assert!(adif.contains("<time_on:4>1430"));
+        assert!(adif.contains("<gridsquare:4>FN42"));
         assert!(adif.contains("<eor>"));
     }
 
@@ -312,4 +594,125 @@ mod tests {
         assert_eq!(qso.band, "20m");
         assert_eq!(qso.mode, "SSB");
     }
+
+    #[test]
+    fn test_parse_adif_with_header() {
+        let adif = "WSJT-X ADIF Export\n<adif_ver:5>3.1.0\n<programid:6>WSJT-X\n<EOH>\n<call:4>W1AW <station_callsign:5>K1ABC <qso_date:8>20240115 <time_on:6>143015 <band:3>20m <mode:3>FT8 <EOR>\n";
+        let qsos = AdifParser::parse_adif(adif).unwrap();
+
+        assert_eq!(qsos.len(), 1);
+        let qso = &qsos[0];
+        assert_eq!(qso.call, "W1AW");
+        assert_eq!(qso.time_on, NaiveTime::from_hms_opt(14, 30, 15).unwrap());
+        assert!(!qso.additional_fields.contains_key("programid"));
+    }
+
+    #[test]
+    fn test_time_precision() {
+        let mut qso = QsoRecord::builder()
+            .call("JA1XY")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 27).unwrap())
+            .time_off(NaiveTime::from_hms_opt(14, 31, 0).unwrap())
+            .band("20m")
+            .mode("FT8")
+            .build();
+
+        let adif = AdifParser::to_adif(&qso);
+        assert!(adif.contains("<time_on:6>143027"));
+        assert!(adif.contains("<time_off:4>1431"));
+
+        let adif = AdifParser::to_adif_with_precision(&qso, TimePrecision::Seconds);
+        assert!(adif.contains("<time_off:6>143100"));
+
+        let adif = AdifParser::to_adif_with_precision(&qso, TimePrecision::Minutes);
+        assert!(adif.contains("<time_on:4>1430"));
+
+        // Both forms round-trip
+        let parsed = &AdifParser::parse_adif(&AdifParser::to_adif(&qso)).unwrap()[0];
+        assert_eq!(parsed.time_on, qso.time_on);
+        assert_eq!(parsed.time_off, qso.time_off);
+
+        qso.time_on = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let adif = AdifParser::to_adif(&qso);
+        assert!(adif.contains("<time_on:4>1430"));
+        let parsed = &AdifParser::parse_adif(&adif).unwrap()[0];
+        assert_eq!(parsed.time_on, qso.time_on);
+    }
+
+    #[test]
+    fn test_typed_fields_roundtrip() {
+        let adif = "<call:6>AO7ABC<station_callsign:5>K1ABC<qso_date:8>20240115<time_on:4>1430<band:2>2m<mode:3>SSB<gridsquare:6>IN80dd<tx_pwr:3>5.5<srx:3>042<dxcc:3>281<cqz:2>14<prop_mode:3>SAT<sat_name:4>AO-7<qsl_rcvd:1>v<qslrdate:8>20240301<app_logger_x:3>foo<eor>";
+        let qsos = AdifParser::parse_adif(adif).unwrap();
+        let qso = &qsos[0];
+
+        assert_eq!(qso.gridsquare.as_deref(), Some("IN80dd"));
+        assert_eq!(qso.tx_pwr, Some(5.5));
+        assert_eq!(qso.srx, Some(42));
+        assert_eq!(qso.dxcc, Some(281));
+        assert_eq!(qso.cqz, Some(14));
+        assert_eq!(qso.prop_mode, Some(PropMode::Satellite));
+        assert_eq!(qso.sat_name.as_deref(), Some("AO-7"));
+        assert_eq!(qso.qsl_rcvd, Some(QslStatus::Verified));
+        assert_eq!(qso.qslrdate, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(qso.additional_fields.len(), 1);
+        assert_eq!(qso.additional_fields["app_logger_x"], "foo");
+
+        let reparsed = AdifParser::parse_adif(&AdifParser::to_adif(qso)).unwrap();
+        assert_eq!(reparsed[0].srx, Some(42));
+        assert_eq!(reparsed[0].prop_mode, Some(PropMode::Satellite));
+        assert_eq!(reparsed[0].qsl_rcvd, Some(QslStatus::Verified));
+        assert_eq!(reparsed[0].additional_fields, qso.additional_fields);
+    }
+
+    #[test]
+    fn test_qso_date_off_without_time_off() {
+        let adif = "<call:4>W1AW<station_callsign:5>K1ABC<qso_date:8>20240115<time_on:4>2358<qso_date_off:8>20240116<band:3>20m<mode:3>SSB<eor>";
+        let qso = &AdifParser::parse_adif(adif).unwrap()[0];
+        assert_eq!(qso.time_off, None);
+        assert!(AdifParser::to_adif(qso).contains("<qso_date_off:8>20240116"));
+    }
+
+    #[test]
+    fn test_typed_fields_invalid() {
+        let base = "<call:4>W1AW<station_callsign:5>K1ABC<qso_date:8>20240115<time_on:4>1430<band:3>20m<mode:3>SSB";
+        for (field, name, value) in [
+            ("<dxcc:3>abc", "dxcc", "abc"),
+            ("<qsl_sent:1>X", "qsl_sent", "X"),
+            ("<tx_pwr:4>100W", "tx_pwr", "100W"),
+            ("<qslsdate:4>2024", "qslsdate", "2024"),
+        ] {
+            let adif = format!("{}{}<eor>", base, field);
+            let qsos = AdifParser::parse_adif(&adif).unwrap();
+            assert_eq!(
+                qsos[0].additional_fields.get(name).map(|s| s.as_str()),
+                Some(value),
+                "{}",
+                field
+            );
+        }
+
+        let adif = format!("{}<dxcc:0><tx_pwr:0><qsl_rcvd:0><eor>", base);
+        let qso = &AdifParser::parse_adif(&adif).unwrap()[0];
+        assert_eq!(qso.dxcc, None);
+        assert_eq!(qso.tx_pwr, None);
+        assert_eq!(qso.qsl_rcvd, None);
+        assert!(qso.additional_fields.is_empty());
+
+        let adif = format!("{}<prop_mode:5>MOON2<eor>", base);
+        let qsos = AdifParser::parse_adif(&adif).unwrap();
+        assert_eq!(
+            qsos[0].prop_mode,
+            Some(PropMode::Other("MOON2".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_band_for_freq() {
+        assert_eq!(band_for_freq(14.074), Some("20m"));
+        assert_eq!(band_for_freq(3.5), Some("80m"));
+        assert_eq!(band_for_freq(144.2), Some("2m"));
+        assert_eq!(band_for_freq(12.0), None);
+    }
 }
@@ -0,0 +1,306 @@
+//! Logbook backup and restore
+//!
+//! [`QrzLogbookClient::backup`] downloads the whole logbook together with a
+//! STATUS snapshot. [`Backup::save`] writes it as a gzip-compressed tar
+//! archive holding two files:
+//!
+//! - `manifest.json`: format version, creation time, record count, logids
+//!   and the STATUS data
+//! - `logbook.adi`: every QSO as ADIF, including QRZ's `app_qrzlog_logid`
+//!
+//! [`QrzLogbookClient::restore`] compares an archive with the current
+//! logbook and re-inserts the QSOs that have gone missing.
+//!
+//! # Example
+//! ```rust,no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use qrz_logbook_api::backup::Backup;
+//!
+//! # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+//! let backup = client.backup().await?;
+//! backup.save("logbook-2024-01-15.tar.gz")?;
+//!
+//! // Later, after a bad bulk edit
+//! let backup = Backup::load("logbook-2024-01-15.tar.gz")?;
+//! let report = client.restore(&backup, false).await?;
+//! println!("Restored {} QSOs", report.restored.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::AdifParser,
+    client::QrzLogbookClient,
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::{FetchOptions, QsoRecord},
+};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// Archive format version written by this crate
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const ADIF_FILE: &str = "logbook.adi";
+const LOGID_FIELD: &str = "app_qrzlog_logid";
+
+/// Metadata stored alongside the QSOs in a backup archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// When the backup was taken
+    pub created_at: DateTime<Utc>,
+    /// Version of this crate that wrote the archive
+    pub crate_version: String,
+    pub qso_count: usize,
+    /// QRZ logids of the backed up QSOs
+    pub logids: Vec<u64>,
+    /// STATUS data at the time of the backup
+    pub status: HashMap<String, String>,
+}
+
+/// A logbook backup
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub manifest: BackupManifest,
+    pub qsos: Vec<QsoRecord>,
+}
+
+impl Backup {
+    /// Build a backup from downloaded QSOs and STATUS data
+    pub fn new(qsos: Vec<QsoRecord>, status: HashMap<String, String>) -> Self {
+        let logids = qsos.iter().filter_map(qrz_logid).collect();
+        Self {
+            manifest: BackupManifest {
+                format_version: FORMAT_VERSION,
+                created_at: Utc::now(),
+                crate_version: env!("CARGO_PKG_VERSION").to_string(),
+                qso_count: qsos.len(),
+                logids,
+                status,
+            },
+            qsos,
+        }
+    }
+
+    /// Write the backup as a `.tar.gz` archive
+    pub fn write<W: Write>(&self, writer: W) -> QrzLogbookResult<()> {
+        let mut archive = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        append_file(&mut archive, MANIFEST_FILE, &manifest)?;
+
+        let adif: String = self.qsos.iter().map(AdifParser::to_adif).collect();
+        append_file(&mut archive, ADIF_FILE, adif.as_bytes())?;
+
+        archive.into_inner()?.finish()?.flush()?;
+        Ok(())
+    }
+
+    /// Read a backup from a `.tar.gz` archive
+    pub fn read<R: Read>(reader: R) -> QrzLogbookResult<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(reader));
+        let mut manifest = None;
+        let mut adif = None;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let mut contents = String::new();
+            match name.as_str() {
+                MANIFEST_FILE => {
+                    entry.read_to_string(&mut contents)?;
+                    manifest = Some(serde_json::from_str::<BackupManifest>(&contents)?);
+                }
+                ADIF_FILE => {
+                    entry.read_to_string(&mut contents)?;
+                    adif = Some(contents);
+                }
+                _ => {}
+            }
+        }
+
+        let manifest = manifest
+            .ok_or_else(|| QrzLogbookError::config("Backup archive has no manifest.json"))?;
+        if manifest.format_version > FORMAT_VERSION {
+            return Err(QrzLogbookError::config(format!(
+                "Backup archive format version {} is newer than supported version {}",
+                manifest.format_version, FORMAT_VERSION
+            )));
+        }
+        let adif =
+            adif.ok_or_else(|| QrzLogbookError::config("Backup archive has no logbook.adi"))?;
+        let qsos = AdifParser::parse_adif(&adif)?;
+        if qsos.len() != manifest.qso_count {
+            return Err(QrzLogbookError::config(format!(
+                "Backup archive holds {} QSOs but the manifest lists {}",
+                qsos.len(),
+                manifest.qso_count
+            )));
+        }
+
+        Ok(Self { manifest, qsos })
+    }
+
+    /// Save the backup to a `.tar.gz` file
+    pub fn save(&self, path: impl AsRef<Path>) -> QrzLogbookResult<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    /// Load a backup from a `.tar.gz` file
+    pub fn load(path: impl AsRef<Path>) -> QrzLogbookResult<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Result of [`QrzLogbookClient::restore`]
+#[derive(Debug, Clone, Default)]
+pub struct RestoreReport {
+    /// Whether this was a dry run that inserted nothing
+    pub dry_run: bool,
+    /// Backed up QSOs that are missing from the logbook
+    pub missing: Vec<QsoRecord>,
+    /// Missing QSOs that were re-inserted, with their new logids
+    pub restored: Vec<(QsoRecord, u64)>,
+    /// Missing QSOs that could not be re-inserted, with the reason
+    pub failed: Vec<(QsoRecord, String)>,
+    /// Number of backed up QSOs still in the logbook
+    pub unchanged: usize,
+    /// QSOs in the logbook that are not in the backup
+    pub added_since_backup: Vec<QsoRecord>,
+}
+
+impl QrzLogbookClient {
+    /// Download the whole logbook and a STATUS snapshot as a [`Backup`]
+    pub async fn backup(&self) -> QrzLogbookResult<Backup> {
+        let status = self.get_status().await?;
+        let qsos = self.fetch_all_qsos(&FetchOptions::all()).await?;
+        Ok(Backup::new(qsos, status.data))
+    }
+
+    /// Re-insert QSOs from a backup that are missing from the logbook
+    ///
+    /// QSOs are matched the way QRZ detects duplicates: callsign, date,
+    /// time on (to the minute), band and mode. With `dry_run` the report
+    /// lists the differences without inserting anything.
+    pub async fn restore(&self, backup: &Backup, dry_run: bool) -> QrzLogbookResult<RestoreReport> {
+        let current = self.fetch_all_qsos(&FetchOptions::all()).await?;
+        let current_keys: HashSet<_> = current.iter().map(QsoRecord::match_key).collect();
+        let backup_keys: HashSet<_> = backup.qsos.iter().map(QsoRecord::match_key).collect();
+
+        let mut report = RestoreReport {
+            dry_run,
+            added_since_backup: current
+                .into_iter()
+                .filter(|qso| !backup_keys.contains(&qso.match_key()))
+                .collect(),
+            ..Default::default()
+        };
+        for qso in &backup.qsos {
+            if current_keys.contains(&qso.match_key()) {
+                report.unchanged += 1;
+            } else {
+                report.missing.push(qso.clone());
+            }
+        }
+
+        if dry_run {
+            return Ok(report);
+        }
+
+        for qso in &report.missing {
+            let mut qso = qso.clone();
+            qso.additional_fields
+                .retain(|name, _| !name.starts_with("app_qrzlog_"));
+            match self.insert_qso(&qso, false).await {
+                Ok(response) => report.restored.push((qso, response.logid)),
+                Err(e) => report.failed.push((qso, e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+}
+
+fn append_file<W: Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    contents: &[u8],
+) -> QrzLogbookResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, name, contents)?;
+    Ok(())
+}
+
+fn qrz_logid(qso: &QsoRecord) -> Option<u64> {
+    qso.additional_fields.get(LOGID_FIELD)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn qso(call: &str, logid: u64) -> QsoRecord {
+        QsoRecord::builder()
+            .call(call)
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band("20m")
+            .mode("SSB")
+            .additional_field(LOGID_FIELD, logid.to_string())
+            .build()
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let status = HashMap::from([("total_qsos".to_string(), "2".to_string())]);
+        let backup = Backup::new(vec![qso("W1AW", 11), qso("DL1AB", 12)], status);
+        assert_eq!(backup.manifest.logids, vec![11, 12]);
+
+        let mut archive = Vec::new();
+        backup.write(&mut archive).unwrap();
+        assert_eq!(&archive[..2], &[0x1f, 0x8b]);
+
+        let restored = Backup::read(archive.as_slice()).unwrap();
+        assert_eq!(restored.manifest.qso_count, 2);
+        assert_eq!(restored.manifest.logids, vec![11, 12]);
+        assert_eq!(restored.manifest.status["total_qsos"], "2");
+        assert_eq!(restored.manifest.created_at, backup.manifest.created_at);
+        assert_eq!(restored.qsos[1].call, "DL1AB");
+        assert_eq!(qrz_logid(&restored.qsos[1]), Some(12));
+    }
+
+    #[test]
+    fn test_read_rejects_bad_archives() {
+        assert!(Backup::read(&b"not an archive"[..]).is_err());
+
+        let mut backup = Backup::new(vec![qso("W1AW", 11)], HashMap::new());
+        backup.manifest.format_version = FORMAT_VERSION + 1;
+        let mut archive = Vec::new();
+        backup.write(&mut archive).unwrap();
+        assert!(matches!(
+            Backup::read(archive.as_slice()),
+            Err(QrzLogbookError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_match_key_ignores_seconds_and_case() {
+        let mut other = qso("w1aw", 99);
+        other.time_on = NaiveTime::from_hms_opt(14, 30, 45).unwrap();
+        other.band = "20M".to_string();
+        assert_eq!(qso("W1AW", 11).match_key(), other.match_key());
+    }
+}
@@ -0,0 +1,163 @@
+//! Blocking QRZ Logbook API client
+//!
+//! Enabled with the `blocking` feature. [`QrzLogbookClient`] offers the same
+//! operations as the async [`crate::QrzLogbookClient`] for synchronous
+//! programs such as CLI scripts or GUI apps with their own event loop. It
+//! drives the async client on a private single-threaded tokio runtime, so
+//! requests, response parsing and ADIF handling are shared.
+//!
+//! The blocking client must not be used from within an async runtime; calls
+//! made from inside one panic, as with `reqwest::blocking`.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::blocking::QrzLogbookClient;
+//! use qrz_logbook_api::FetchOptions;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
+//!
+//!     let status = client.get_status()?;
+//!     println!("Status: {:?}", status.data);
+//!
+//!     let qsos = client.fetch_all_qsos(&FetchOptions::new().band("20m"))?;
+//!     println!("Retrieved {} QSOs", qsos.len());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    client,
+    error::QrzLogbookResult,
+    models::{
+        DeleteResponse, FetchOptions, FetchResponse, InsertResponse, QsoRecord, StatusResponse,
+    },
+    secret::ApiKey,
+    transport::Transport,
+};
+use tokio::runtime::{Builder, Runtime};
+
+/// Blocking QRZ Logbook API client
+pub struct QrzLogbookClient {
+    inner: client::QrzLogbookClient,
+    runtime: Runtime,
+}
+
+impl QrzLogbookClient {
+    /// Create a new blocking client
+    ///
+    /// Takes the same arguments as [`crate::QrzLogbookClient::new`].
+    pub fn new(
+        api_key: impl Into<ApiKey>,
+        user_agent: impl Into<String>,
+    ) -> QrzLogbookResult<Self> {
+        Self::from_async(client::QrzLogbookClient::new(api_key, user_agent)?)
+    }
+
+    /// Create a blocking client that sends requests through a custom transport
+    pub fn with_transport(
+        api_key: impl Into<ApiKey>,
+        user_agent: impl Into<String>,
+        transport: impl Transport + 'static,
+    ) -> QrzLogbookResult<Self> {
+        Self::from_async(client::QrzLogbookClient::with_transport(
+            api_key, user_agent, transport,
+        )?)
+    }
+
+    /// Wrap an existing async client
+    pub fn from_async(inner: client::QrzLogbookClient) -> QrzLogbookResult<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The async client used for requests
+    pub fn async_client(&self) -> &client::QrzLogbookClient {
+        &self.inner
+    }
+
+    /// Insert a QSO record into the logbook
+    ///
+    /// See [`crate::QrzLogbookClient::insert_qso`].
+    pub fn insert_qso(&self, qso: &QsoRecord, replace: bool) -> QrzLogbookResult<InsertResponse> {
+        self.runtime.block_on(self.inner.insert_qso(qso, replace))
+    }
+
+    /// Insert a batch of QSO records
+    ///
+    /// See [`crate::QrzLogbookClient::insert_qsos`].
+    pub fn insert_qsos(
+        &self,
+        qsos: &[QsoRecord],
+        replace: bool,
+    ) -> Vec<QrzLogbookResult<InsertResponse>> {
+        self.runtime.block_on(self.inner.insert_qsos(qsos, replace))
+    }
+
+    /// Delete QSO records from the logbook
+    ///
+    /// See [`crate::QrzLogbookClient::delete_qsos`].
+    pub fn delete_qsos(&self, logids: Vec<u64>) -> QrzLogbookResult<DeleteResponse> {
+        self.runtime.block_on(self.inner.delete_qsos(logids))
+    }
+
+    /// Get logbook status and statistics
+    ///
+    /// See [`crate::QrzLogbookClient::get_status`].
+    pub fn get_status(&self) -> QrzLogbookResult<StatusResponse> {
+        self.runtime.block_on(self.inner.get_status())
+    }
+
+    /// Fetch QSO records from the logbook
+    ///
+    /// See [`crate::QrzLogbookClient::fetch_qsos`].
+    pub fn fetch_qsos(&self, options: &FetchOptions) -> QrzLogbookResult<FetchResponse> {
+        self.runtime.block_on(self.inner.fetch_qsos(options))
+    }
+
+    /// Fetch QSOs with automatic paging
+    ///
+    /// See [`crate::QrzLogbookClient::fetch_all_qsos`].
+    pub fn fetch_all_qsos(&self, options: &FetchOptions) -> QrzLogbookResult<Vec<QsoRecord>> {
+        self.runtime.block_on(self.inner.fetch_all_qsos(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_blocking_client_shares_parsing() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&LOGID=12345&COUNT=1",
+            "RESULT=OK&DATA=total_qsos%3D1",
+            "RESULT=OK&COUNT=1&LOGIDS=12345&ADIF=%3Ccall%3A4%3EW1AW%3Cstation_callsign%3A5%3EK1ABC%3Cqso_date%3A8%3E20240115%3Ctime_on%3A4%3E1430%3Cband%3A3%3E20m%3Cmode%3A3%3ESSB%3Ceor%3E",
+        ]);
+        let client = QrzLogbookClient::with_transport(
+            "ABCD-1234-EF56-7890",
+            "TestSuite/1.0.0 (N0CALL)",
+            transport.clone(),
+        )
+        .unwrap();
+
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band("20m")
+            .mode("SSB")
+            .build();
+
+        assert_eq!(client.insert_qso(&qso, false).unwrap().logid, 12345);
+        assert_eq!(client.get_status().unwrap().data["total_qsos"], "1");
+
+        let qsos = client.fetch_all_qsos(&FetchOptions::all()).unwrap();
+        assert_eq!(qsos.len(), 1);
+        assert_eq!(qsos[0].call, "W1AW");
+        assert_eq!(transport.requests().len(), 3);
+    }
+}
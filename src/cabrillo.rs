@@ -0,0 +1,763 @@
+//! Cabrillo 3.0 contest log support
+//!
+//! [`CabrilloWriter`] turns fetched QSO records into a Cabrillo log for contest
+//! submission, and [`CabrilloReader`] parses a Cabrillo log back into QSO
+//! records for upload.
+//!
+//! Exchanges are mapped onto the ADIF contest fields: the sent exchange is the
+//! RST followed by `STX_STRING` (or `STX`), and the received exchange is the
+//! RST followed by `SRX_STRING` (or `SRX`).
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::{
+//!     cabrillo::{CabrilloHeader, CabrilloWriter},
+//!     FetchOptions,
+//! };
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+//! let qsos = client.fetch_all_qsos(&FetchOptions::new()).await?;
+//!
+//! let mut header = CabrilloHeader::new("CQ-WW-CW", "K1ABC");
+//! header.category_operator = Some("SINGLE-OP".to_string());
+//! header.claimed_score = Some(123_456);
+//!
+//! let log = CabrilloWriter::new(header).write(&qsos)?;
+//! std::fs::write("k1abc.log", log)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::{band_edges, band_for_freq, AdifParser},
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::QsoRecord,
+};
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::HashMap;
+
+/// Cabrillo band designators for 50 MHz and above
+const VHF_BANDS: &[(&str, &str)] = &[
+    ("6m", "50"),
+    ("4m", "70"),
+    ("2m", "144"),
+    ("1.25m", "222"),
+    ("70cm", "432"),
+    ("33cm", "902"),
+    ("23cm", "1.2G"),
+    ("13cm", "2.3G"),
+    ("9cm", "3.4G"),
+    ("6cm", "5.7G"),
+    ("3cm", "10G"),
+    ("1.25cm", "24G"),
+    ("6mm", "47G"),
+    ("4mm", "75G"),
+    ("2.5mm", "122G"),
+    ("2mm", "134G"),
+    ("1mm", "241G"),
+];
+
+/// Cabrillo log header
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CabrilloHeader {
+    pub contest: String,
+    pub callsign: String,
+    pub location: Option<String>,
+    pub category_operator: Option<String>,
+    pub category_assisted: Option<String>,
+    pub category_band: Option<String>,
+    pub category_mode: Option<String>,
+    pub category_power: Option<String>,
+    pub category_station: Option<String>,
+    pub category_transmitter: Option<String>,
+    pub category_overlay: Option<String>,
+    pub category_time: Option<String>,
+    pub claimed_score: Option<u64>,
+    pub club: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub grid_locator: Option<String>,
+    pub address: Vec<String>,
+    pub operators: Vec<String>,
+    pub soapbox: Vec<String>,
+    pub created_by: Option<String>,
+    /// Tags not covered by the fields above, in file order
+    pub other_tags: Vec<(String, String)>,
+}
+
+impl CabrilloHeader {
+    /// Create a header for a contest and station callsign
+    pub fn new(contest: impl Into<String>, callsign: impl Into<String>) -> Self {
+        Self {
+            contest: contest.into(),
+            callsign: callsign.into(),
+            ..Default::default()
+        }
+    }
+
+    fn tags(&self) -> Vec<(&'static str, String)> {
+        let mut tags = vec![
+            ("CALLSIGN", self.callsign.clone()),
+            ("CONTEST", self.contest.clone()),
+        ];
+        fn push(tags: &mut Vec<(&'static str, String)>, tag: &'static str, value: &Option<String>) {
+            if let Some(value) = value {
+                tags.push((tag, value.clone()));
+            }
+        }
+
+        push(&mut tags, "LOCATION", &self.location);
+        push(&mut tags, "CATEGORY-OPERATOR", &self.category_operator);
+        push(&mut tags, "CATEGORY-ASSISTED", &self.category_assisted);
+        push(&mut tags, "CATEGORY-BAND", &self.category_band);
+        push(&mut tags, "CATEGORY-MODE", &self.category_mode);
+        push(&mut tags, "CATEGORY-POWER", &self.category_power);
+        push(&mut tags, "CATEGORY-STATION", &self.category_station);
+        push(
+            &mut tags,
+            "CATEGORY-TRANSMITTER",
+            &self.category_transmitter,
+        );
+        push(&mut tags, "CATEGORY-OVERLAY", &self.category_overlay);
+        push(&mut tags, "CATEGORY-TIME", &self.category_time);
+        push(
+            &mut tags,
+            "CLAIMED-SCORE",
+            &self.claimed_score.map(|s| s.to_string()),
+        );
+        push(&mut tags, "CLUB", &self.club);
+        push(&mut tags, "NAME", &self.name);
+        for line in &self.address {
+            tags.push(("ADDRESS", line.clone()));
+        }
+        push(&mut tags, "EMAIL", &self.email);
+        push(&mut tags, "GRID-LOCATOR", &self.grid_locator);
+        if !self.operators.is_empty() {
+            tags.push(("OPERATORS", self.operators.join(" ")));
+        }
+        for line in &self.soapbox {
+            tags.push(("SOAPBOX", line.clone()));
+        }
+        tags.push((
+            "CREATED-BY",
+            self.created_by.clone().unwrap_or_else(|| {
+                format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+            }),
+        ));
+
+        tags
+    }
+
+    fn set_tag(&mut self, tag: &str, value: &str) -> QrzLogbookResult<()> {
+        let value = value.trim().to_string();
+        let optional = || (!value.is_empty()).then(|| value.clone());
+
+        match tag {
+            "CALLSIGN" => self.callsign = value,
+            "CONTEST" => self.contest = value,
+            "LOCATION" => self.location = optional(),
+            "CATEGORY-OPERATOR" => self.category_operator = optional(),
+            "CATEGORY-ASSISTED" => self.category_assisted = optional(),
+            "CATEGORY-BAND" => self.category_band = optional(),
+            "CATEGORY-MODE" => self.category_mode = optional(),
+            "CATEGORY-POWER" => self.category_power = optional(),
+            "CATEGORY-STATION" => self.category_station = optional(),
+            "CATEGORY-TRANSMITTER" => self.category_transmitter = optional(),
+            "CATEGORY-OVERLAY" => self.category_overlay = optional(),
+            "CATEGORY-TIME" => self.category_time = optional(),
+            "CLAIMED-SCORE" => {
+                self.claimed_score = optional()
+                    .map(|s| s.replace(',', "").parse())
+                    .transpose()
+                    .map_err(|_| {
+                        QrzLogbookError::protocol(format!("Invalid CLAIMED-SCORE: {}", value))
+                    })?
+            }
+            "CLUB" => self.club = optional(),
+            "NAME" => self.name = optional(),
+            "ADDRESS" => self.address.push(value),
+            "EMAIL" => self.email = optional(),
+            "GRID-LOCATOR" => self.grid_locator = optional(),
+            "OPERATORS" => self
+                .operators
+                .extend(value.split_whitespace().map(|s| s.to_string())),
+            "SOAPBOX" => self.soapbox.push(value),
+            "CREATED-BY" => self.created_by = optional(),
+            _ => self.other_tags.push((tag.to_string(), value)),
+        }
+
+        Ok(())
+    }
+}
+
+/// Parsed Cabrillo log
+#[derive(Debug, Clone)]
+pub struct CabrilloLog {
+    pub header: CabrilloHeader,
+    pub qsos: Vec<QsoRecord>,
+}
+
+/// Cabrillo 3.0 log writer
+#[derive(Debug, Clone)]
+pub struct CabrilloWriter {
+    header: CabrilloHeader,
+    include_rst: bool,
+    default_rst: bool,
+}
+
+impl CabrilloWriter {
+    /// Create a writer for a log header
+    pub fn new(header: CabrilloHeader) -> Self {
+        Self {
+            header,
+            include_rst: true,
+            default_rst: false,
+        }
+    }
+
+    /// Whether exchanges start with the RST (default `true`)
+    ///
+    /// Contests such as Sweepstakes don't exchange signal reports.
+    pub fn include_rst(mut self, include_rst: bool) -> Self {
+        self.include_rst = include_rst;
+        self
+    }
+
+    /// Write 59 (phone) or 599 (other modes) for QSOs without an RST
+    /// (default `false`, which makes a missing RST an error)
+    pub fn default_rst(mut self, default_rst: bool) -> Self {
+        self.default_rst = default_rst;
+        self
+    }
+
+    /// Write a Cabrillo log containing the QSOs in chronological order
+    pub fn write(&self, qsos: &[QsoRecord]) -> QrzLogbookResult<String> {
+        let mut log = String::from("START-OF-LOG: 3.0\n");
+        let tags = self.header.tags().into_iter();
+        let other_tags = self
+            .header
+            .other_tags
+            .iter()
+            .map(|(t, v)| (t.as_str(), v.clone()));
+        for (tag, value) in tags.chain(other_tags) {
+            if value.is_empty() {
+                log.push_str(&format!("{}:\n", tag));
+            } else {
+                log.push_str(&format!("{}: {}\n", tag, value));
+            }
+        }
+
+        let mut sorted: Vec<&QsoRecord> = qsos.iter().collect();
+        sorted.sort_by_key(|qso| (qso.qso_date, qso.time_on));
+        for qso in sorted {
+            log.push_str(&self.qso_line(qso)?);
+            log.push('\n');
+        }
+
+        log.push_str("END-OF-LOG:\n");
+        Ok(log)
+    }
+
+    fn qso_line(&self, qso: &QsoRecord) -> QrzLogbookResult<String> {
+        let fields: HashMap<String, String> = AdifParser::to_fields(qso).into_iter().collect();
+        let mode = cabrillo_mode(&qso.mode);
+        let my_call = if qso.station_callsign.is_empty() {
+            &self.header.callsign
+        } else {
+            &qso.station_callsign
+        };
+
+        let mut line = format!(
+            "QSO: {:>5} {} {} {} {:<13}",
+            cabrillo_freq(qso)?,
+            mode,
+            qso.qso_date.format("%Y-%m-%d"),
+            qso.time_on.format("%H%M"),
+            my_call
+        );
+        for token in self.exchange(qso, qso.rst_sent.as_deref(), mode, &fields, "stx")? {
+            line.push_str(&format!(" {:<6}", token));
+        }
+        line.push_str(&format!(" {:<13}", qso.call));
+        for token in self.exchange(qso, qso.rst_rcvd.as_deref(), mode, &fields, "srx")? {
+            line.push_str(&format!(" {:<6}", token));
+        }
+
+        Ok(line.trim_end().to_string())
+    }
+
+    /// Exchange tokens: RST followed by `<prefix>_string` or `<prefix>`
+    fn exchange(
+        &self,
+        qso: &QsoRecord,
+        rst: Option<&str>,
+        mode: &str,
+        fields: &HashMap<String, String>,
+        prefix: &str,
+    ) -> QrzLogbookResult<Vec<String>> {
+        let mut tokens = Vec::new();
+        if self.include_rst {
+            let rst = match rst {
+                Some(rst) => rst,
+                None if self.default_rst && (mode == "PH" || mode == "FM") => "59",
+                None if self.default_rst => "599",
+                None => {
+                    let field = if prefix == "stx" {
+                        "rst_sent"
+                    } else {
+                        "rst_rcvd"
+                    };
+                    return Err(QrzLogbookError::invalid_params(format!(
+                        "QSO with {} at {} {} has no {}",
+                        qso.call,
+                        qso.qso_date,
+                        qso.time_on.format("%H%M"),
+                        field
+                    )));
+                }
+            };
+            tokens.push(rst.to_string());
+        }
+
+        let exchange = fields
+            .get(&format!("{}_string", prefix))
+            .or_else(|| fields.get(prefix))
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .unwrap_or("-");
+        tokens.extend(exchange.split_whitespace().map(|s| s.to_string()));
+
+        Ok(tokens)
+    }
+}
+
+/// Cabrillo 3.0 log reader
+#[derive(Debug, Clone)]
+pub struct CabrilloReader {
+    include_rst: bool,
+    sent_exchange_fields: Option<usize>,
+    digital_mode: String,
+}
+
+impl Default for CabrilloReader {
+    fn default() -> Self {
+        Self {
+            include_rst: true,
+            sent_exchange_fields: None,
+            digital_mode: "FT8".to_string(),
+        }
+    }
+}
+
+impl CabrilloReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether exchanges start with the RST (default `true`)
+    pub fn include_rst(mut self, include_rst: bool) -> Self {
+        self.include_rst = include_rst;
+        self
+    }
+
+    /// Number of sent exchange fields on each QSO line, including the RST
+    ///
+    /// By default sent and received exchanges are assumed to have the same
+    /// number of fields, which holds for most contests.
+    pub fn sent_exchange_fields(mut self, count: usize) -> Self {
+        self.sent_exchange_fields = Some(count);
+        self
+    }
+
+    /// ADIF mode for the Cabrillo `DG` mode (default "FT8")
+    pub fn digital_mode(mut self, mode: impl Into<String>) -> Self {
+        self.digital_mode = mode.into();
+        self
+    }
+
+    /// Parse a Cabrillo log
+    ///
+    /// `X-QSO` lines (QSOs excluded from scoring) are skipped.
+    pub fn parse(&self, cabrillo: &str) -> QrzLogbookResult<CabrilloLog> {
+        let mut header = CabrilloHeader::default();
+        let mut qsos = Vec::new();
+
+        for (index, line) in cabrillo.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let at_line = |e| match e {
+                QrzLogbookError::Protocol(msg) | QrzLogbookError::AdifParse(msg) => {
+                    QrzLogbookError::protocol(format!("Cabrillo line {}: {}", index + 1, msg))
+                }
+                e => e,
+            };
+
+            let Some((tag, value)) = line.split_once(':') else {
+                return Err(at_line(QrzLogbookError::protocol("missing tag")));
+            };
+
+            match tag.trim().to_uppercase().as_str() {
+                "START-OF-LOG" | "END-OF-LOG" | "X-QSO" => {}
+                "QSO" => qsos.push(self.parse_qso(value).map_err(at_line)?),
+                tag => header.set_tag(tag, value).map_err(at_line)?,
+            }
+        }
+
+        Ok(CabrilloLog { header, qsos })
+    }
+
+    fn parse_qso(&self, line: &str) -> QrzLogbookResult<QsoRecord> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() < 6 {
+            return Err(QrzLogbookError::protocol("Too few QSO fields"));
+        }
+
+        let (freq, mode, date, time) = (tokens[0], tokens[1], tokens[2], tokens[3]);
+        let rest = &tokens[4..];
+
+        // mycall, sent exchange, call, received exchange and optional transmitter ID
+        let sent = match self.sent_exchange_fields {
+            Some(count) => count,
+            None => (rest.len() - 2 - rest.len() % 2) / 2,
+        };
+        if rest.len() < sent + 2 {
+            return Err(QrzLogbookError::protocol("Too few QSO fields"));
+        }
+        let received = match self.sent_exchange_fields {
+            Some(_) => rest.len() - sent - 2,
+            None => sent,
+        };
+
+        let mut fields = HashMap::new();
+        fields.insert("station_callsign".to_string(), rest[0].to_uppercase());
+        fields.insert("call".to_string(), rest[sent + 1].to_uppercase());
+
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| QrzLogbookError::protocol(format!("Invalid date: {}", date)))?;
+        fields.insert("qso_date".to_string(), date.format("%Y%m%d").to_string());
+        let time = NaiveTime::parse_from_str(time, "%H%M")
+            .map_err(|_| QrzLogbookError::protocol(format!("Invalid time: {}", time)))?;
+        fields.insert("time_on".to_string(), time.format("%H%M").to_string());
+
+        let (band, freq_mhz) = parse_freq(freq)?;
+        fields.insert("band".to_string(), band.to_string());
+        if let Some(freq) = freq_mhz {
+            fields.insert("freq".to_string(), freq.to_string());
+        }
+
+        let mode = match mode.to_uppercase().as_str() {
+            "CW" => "CW".to_string(),
+            "PH" => "SSB".to_string(),
+            "FM" => "FM".to_string(),
+            "RY" => "RTTY".to_string(),
+            "DG" => self.digital_mode.clone(),
+            other => {
+                return Err(QrzLogbookError::protocol(format!(
+                    "Invalid mode: {}",
+                    other
+                )))
+            }
+        };
+        fields.insert("mode".to_string(), mode);
+
+        self.insert_exchange(&mut fields, &rest[1..sent + 1], "rst_sent", "stx");
+        self.insert_exchange(
+            &mut fields,
+            &rest[sent + 2..sent + 2 + received],
+            "rst_rcvd",
+            "srx",
+        );
+
+        AdifParser::fields_to_qso(fields)
+    }
+
+    fn insert_exchange(
+        &self,
+        fields: &mut HashMap<String, String>,
+        tokens: &[&str],
+        rst_field: &str,
+        prefix: &str,
+    ) {
+        let mut tokens = tokens;
+        if self.include_rst {
+            if let Some((rst, remaining)) = tokens.split_first() {
+                fields.insert(rst_field.to_string(), rst.to_string());
+                tokens = remaining;
+            }
+        }
+
+        match tokens {
+            [] | ["-"] => {}
+            [serial] if serial.parse::<u32>().is_ok() => {
+                fields.insert(prefix.to_string(), serial.to_string());
+            }
+            tokens => {
+                fields.insert(format!("{}_string", prefix), tokens.join(" "));
+            }
+        }
+    }
+}
+
+/// Cabrillo frequency: kHz below 30 MHz, band designator above
+fn cabrillo_freq(qso: &QsoRecord) -> QrzLogbookResult<String> {
+    if let Some(freq) = qso.freq.filter(|f| *f < 30.0) {
+        return Ok(format!("{:.0}", freq * 1000.0));
+    }
+
+    if let Some((_, designator)) = VHF_BANDS
+        .iter()
+        .find(|(band, _)| band.eq_ignore_ascii_case(&qso.band))
+    {
+        return Ok(designator.to_string());
+    }
+
+    band_edges(&qso.band)
+        .filter(|(lower, _)| *lower < 30.0)
+        .map(|(lower, _)| format!("{:.0}", lower * 1000.0))
+        .ok_or_else(|| {
+            QrzLogbookError::invalid_params(format!(
+                "QSO with {} has no Cabrillo frequency for band {}",
+                qso.call, qso.band
+            ))
+        })
+}
+
+fn parse_freq(freq: &str) -> QrzLogbookResult<(&'static str, Option<f64>)> {
+    if let Some((band, _)) = VHF_BANDS
+        .iter()
+        .find(|(_, designator)| designator.eq_ignore_ascii_case(freq))
+    {
+        return Ok((band, None));
+    }
+
+    let khz: f64 = freq
+        .parse()
+        .map_err(|_| QrzLogbookError::protocol(format!("Invalid frequency: {}", freq)))?;
+    let mhz = khz / 1000.0;
+    let band = band_for_freq(mhz)
+        .ok_or_else(|| QrzLogbookError::protocol(format!("Invalid frequency: {}", freq)))?;
+
+    Ok((band, Some(mhz)))
+}
+
+fn cabrillo_mode(mode: &str) -> &'static str {
+    match mode.to_uppercase().as_str() {
+        "CW" => "CW",
+        "SSB" | "USB" | "LSB" | "AM" => "PH",
+        "FM" => "FM",
+        "RTTY" => "RY",
+        _ => "DG",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contest_qso(call: &str, time: (u32, u32), freq: f64, band: &str, srx: &str) -> QsoRecord {
+        QsoRecord::builder()
+            .call(call)
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 11, 23).unwrap())
+            .time_on(NaiveTime::from_hms_opt(time.0, time.1, 0).unwrap())
+            .band(band)
+            .mode("CW")
+            .freq(freq)
+            .rst_sent("599")
+            .rst_rcvd("599")
+            .stx_string("5")
+            .srx_string(srx)
+            .build()
+    }
+
+    #[test]
+    fn test_write_cabrillo() {
+        let mut header = CabrilloHeader::new("CQ-WW-CW", "K1ABC");
+        header.category_operator = Some("SINGLE-OP".to_string());
+        header.category_band = Some("ALL".to_string());
+        header.claimed_score = Some(1234);
+        header.operators = vec!["K1ABC".to_string(), "W1AW".to_string()];
+        header.created_by = Some("TestSuite 1.0".to_string());
+        header.address = vec![String::new()];
+
+        let qsos = vec![
+            contest_qso("JA1XY", (0, 2), 14.0251, "20m", "25"),
+            contest_qso("DL1AB", (0, 1), 7.011, "40m", "14"),
+        ];
+        let log = CabrilloWriter::new(header).write(&qsos).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+
+        assert_eq!(lines[0], "START-OF-LOG: 3.0");
+        assert!(lines.contains(&"CALLSIGN: K1ABC"));
+        assert!(lines.contains(&"CONTEST: CQ-WW-CW"));
+        assert!(lines.contains(&"CATEGORY-OPERATOR: SINGLE-OP"));
+        assert!(lines.contains(&"CLAIMED-SCORE: 1234"));
+        assert!(lines.contains(&"OPERATORS: K1ABC W1AW"));
+        assert!(lines.contains(&"CREATED-BY: TestSuite 1.0"));
+        assert!(lines.contains(&"ADDRESS:"));
+        assert_eq!(lines.last(), Some(&"END-OF-LOG:"));
+
+        let qso_lines: Vec<&&str> = lines.iter().filter(|l| l.starts_with("QSO:")).collect();
+        assert_eq!(
+            *qso_lines[0],
+            "QSO:  7011 CW 2024-11-23 0001 K1ABC         599    5      DL1AB         599    14"
+        );
+        assert!(qso_lines[1].starts_with("QSO: 14025 CW 2024-11-23 0002 K1ABC"));
+    }
+
+    #[test]
+    fn test_write_vhf_band_designator() {
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 6, 8).unwrap())
+            .time_on(NaiveTime::from_hms_opt(18, 0, 0).unwrap())
+            .band("2m")
+            .mode("FM")
+            .freq(146.52)
+            .srx_string("FN31")
+            .stx_string("FN42")
+            .build();
+
+        let log = CabrilloWriter::new(CabrilloHeader::new("ARRL-VHF-JUN", "K1ABC"))
+            .include_rst(false)
+            .write(&[qso])
+            .unwrap();
+        assert!(
+            log.contains("QSO:   144 FM 2024-06-08 1800 K1ABC         FN42   W1AW          FN31")
+        );
+    }
+
+    #[test]
+    fn test_write_without_frequency() {
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("K1ABC")
+            .band("20m")
+            .mode("SSB")
+            .build();
+        let log = CabrilloWriter::new(CabrilloHeader::new("TEST", "K1ABC"))
+            .default_rst(true)
+            .write(std::slice::from_ref(&qso))
+            .unwrap();
+        let qso_line = log.lines().find(|l| l.starts_with("QSO:")).unwrap();
+        assert!(qso_line.starts_with("QSO: 14000 PH"));
+        assert!(qso_line.contains("K1ABC         59     -      W1AW          59"));
+
+        let err = CabrilloWriter::new(CabrilloHeader::new("TEST", "K1ABC"))
+            .write(&[qso])
+            .unwrap_err();
+        assert!(err.to_string().contains("rst_sent"));
+
+        let unknown = QsoRecord::builder().call("W1AW").band("99m").build();
+        assert!(CabrilloWriter::new(CabrilloHeader::new("TEST", "K1ABC"))
+            .write(&[unknown])
+            .is_err());
+    }
+
+    #[test]
+    fn test_cabrillo_roundtrip() {
+        let qsos = vec![
+            contest_qso("JA1XY", (0, 2), 14.025, "20m", "25"),
+            contest_qso("DL1AB", (0, 1), 7.011, "40m", "14"),
+        ];
+        let mut header = CabrilloHeader::new("CQ-WW-CW", "K1ABC");
+        header.soapbox = vec!["Great conditions".to_string(), "73!".to_string()];
+        let log = CabrilloWriter::new(header.clone()).write(&qsos).unwrap();
+
+        let parsed = CabrilloReader::new().parse(&log).unwrap();
+        assert_eq!(parsed.header.contest, "CQ-WW-CW");
+        assert_eq!(parsed.header.soapbox, header.soapbox);
+        assert_eq!(parsed.qsos.len(), 2);
+
+        let dl1ab = &parsed.qsos[0];
+        assert_eq!(dl1ab.call, "DL1AB");
+        assert_eq!(dl1ab.station_callsign, "K1ABC");
+        assert_eq!(dl1ab.band, "40m");
+        assert_eq!(dl1ab.freq, Some(7.011));
+        assert_eq!(dl1ab.rst_sent.as_deref(), Some("599"));
+        assert_eq!(dl1ab.srx, Some(14));
+        assert_eq!(dl1ab.stx, Some(5));
+    }
+
+    #[test]
+    fn test_parse_cabrillo() {
+        let log = "START-OF-LOG: 3.0
+CALLSIGN: K1ABC
+CONTEST: ARRL-SS-CW
+CLAIMED-SCORE: 12,345
+OPERATORS: K1ABC
+OPERATORS: W1AW
+X-CUSTOM: hello
+QSO:  3525 CW 2024-11-02 2101 K1ABC         1 A 72 CT       W1AW          15 B 99 CT
+X-QSO:  3526 CW 2024-11-02 2102 K1ABC       2 A 72 CT       N0CALL        1 A 01 MN
+QSO: 144 DG 2024-11-02 2105 K1ABC 3 A 72 CT JA1XY 2 Q 88 JA 1
+END-OF-LOG:
+";
+
+        let parsed = CabrilloReader::new()
+            .include_rst(false)
+            .digital_mode("FT4")
+            .parse(log)
+            .unwrap();
+        assert_eq!(parsed.header.claimed_score, Some(12345));
+        assert_eq!(parsed.header.operators, vec!["K1ABC", "W1AW"]);
+        assert_eq!(
+            parsed.header.other_tags,
+            vec![("X-CUSTOM".to_string(), "hello".to_string())]
+        );
+        assert_eq!(parsed.qsos.len(), 2);
+
+        let w1aw = &parsed.qsos[0];
+        assert_eq!(w1aw.call, "W1AW");
+        assert_eq!(w1aw.band, "80m");
+        assert_eq!(w1aw.rst_sent, None);
+        assert_eq!(w1aw.srx_string.as_deref(), Some("15 B 99 CT"));
+
+        let ja1xy = &parsed.qsos[1];
+        assert_eq!(ja1xy.band, "2m");
+        assert_eq!(ja1xy.mode, "FT4");
+        assert_eq!(ja1xy.freq, None);
+        assert_eq!(ja1xy.srx_string.as_deref(), Some("2 Q 88 JA"));
+    }
+
+    #[test]
+    fn test_parse_asymmetric_exchange() {
+        let log = "QSO: 14025 CW 2024-11-23 0001 K1ABC 599 5 JA1XY 599 25 JA";
+        let parsed = CabrilloReader::new()
+            .sent_exchange_fields(2)
+            .parse(log)
+            .unwrap();
+        assert_eq!(parsed.qsos[0].srx_string.as_deref(), Some("25 JA"));
+    }
+
+    #[test]
+    fn test_parse_invalid_qso_lines() {
+        let result =
+            CabrilloReader::new().parse("QSO: 14025 XX 2024-11-23 0001 K1ABC 599 5 JA1XY 599 25");
+        assert!(matches!(
+            result,
+            Err(QrzLogbookError::Protocol(msg)) if msg == "Cabrillo line 1: Invalid mode: XX"
+        ));
+
+        assert!(CabrilloReader::new()
+            .parse("QSO: 14025 CW 2024-13-23 0001 K1ABC 599 5 JA1XY 599 25")
+            .is_err());
+        assert!(CabrilloReader::new().parse("QSO: 14025 CW").is_err());
+        assert!(CabrilloReader::new().parse("not cabrillo").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_header() {
+        let result =
+            CabrilloReader::new().parse("START-OF-LOG: 3.0\nCLAIMED-SCORE: lots\nEND-OF-LOG:");
+        assert!(matches!(
+            result,
+            Err(QrzLogbookError::Protocol(msg)) if msg == "Cabrillo line 2: Invalid CLAIMED-SCORE: lots"
+        ));
+    }
+}
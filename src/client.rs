@@ -1,19 +1,36 @@
 use crate::{
-    adif::AdifParser,
+    adif::{AdifParser, TimePrecision},
     error::{QrzLogbookError, QrzLogbookResult},
+    metrics::{MetricsHook, REQUESTS_TOTAL, REQUEST_DURATION_SECONDS},
+    middleware::Middleware,
     models::{
-        DeleteResponse, FetchOptions, FetchResponse, InsertResponse, QsoRecord, StatusResponse,
+        CallsignPolicy, DeleteOptions, DeleteReport, DeleteResponse, FetchOptions, FetchResponse,
+        FetchType, InsertResponse, KeyInfo, QsoPatch, QsoRecord, StatusResponse, UpdateResponse,
     },
+    profile::base_callsign,
+    query::QsoFilter,
+    secret::ApiKey,
+    sync::{self, HighWaterMark, LogidDiff},
+    transport::{ReqwestTransport, Transport, TransportRequest},
 };
-use reqwest::Client;
-use std::collections::HashMap;
-
-const API_ENDPOINT: &str = "https://logbook.qrz.com/api";
+use chrono::{DateTime, Utc};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tracing::{field, Instrument, Span};
 
 /// QRZ Logbook API client
 pub struct QrzLogbookClient {
-    client: Client,
-    api_key: String,
+    transport: Arc<dyn Transport>,
+    metrics: Option<Arc<dyn MetricsHook>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    callsign_policy: CallsignPolicy,
+    time_precision: TimePrecision,
+    owner: Mutex<Option<String>>,
+    api_key: ApiKey,
     #[allow(dead_code)] // User agent is used for requests, but not needed in all methods
     user_agent: String,
 }
@@ -22,51 +39,116 @@ impl QrzLogbookClient {
     /// Create a new QRZ Logbook client
     ///
     /// # Arguments
-    /// * `api_key` - Your QRZ API access key
+    /// * `api_key` - Your QRZ API access key (`XXXX-XXXX-XXXX-XXXX`; case and whitespace are normalized)
     /// * `user_agent` - Identifiable user agent (max 128 chars, should include callsign)
     ///
     /// # Example
     /// ```rust,no_run
     /// use qrz_logbook_api::QrzLogbookClient;
     ///
-    /// let client = QrzLogbookClient::new("YOUR-API-KEY", "MyApp/1.0.0 (YOURCALL)").unwrap();
+    /// let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)").unwrap();
     /// ```
     pub fn new(
-        api_key: impl Into<String>,
+        api_key: impl Into<ApiKey>,
         user_agent: impl Into<String>,
     ) -> QrzLogbookResult<Self> {
-        let api_key = api_key.into();
+        let api_key = api_key.into().normalize()?;
         let user_agent = user_agent.into();
+        validate_user_agent(&user_agent)?;
 
-        // Validate API key format (basic validation)
-        if api_key.is_empty() || api_key.len() < 10 {
-            return Err(QrzLogbookError::InvalidKey);
-        }
-
-        // Validate user agent
-        if user_agent.is_empty() || user_agent.len() > 128 {
-            return Err(QrzLogbookError::InvalidUserAgent);
-        }
+        let transport = ReqwestTransport::new(&user_agent)?;
+        Self::with_transport(api_key, user_agent, transport)
+    }
 
-        // Check for generic user agents
-        let lower_ua = user_agent.to_lowercase();
-        if lower_ua.contains("python-requests")
-            || lower_ua.contains("node-fetch")
-            || lower_ua == "curl"
-            || lower_ua == "wget"
-        {
-            return Err(QrzLogbookError::InvalidUserAgent);
-        }
+    /// Create a client that sends requests through a custom transport
+    ///
+    /// The user agent is validated as in [`new`](Self::new), but it is up to
+    /// the transport to send it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use qrz_logbook_api::transport::ReqwestTransport;
+    /// use qrz_logbook_api::QrzLogbookClient;
+    ///
+    /// let user_agent = "MyApp/1.0.0 (YOURCALL)";
+    /// let transport = ReqwestTransport::new(user_agent)
+    ///     .unwrap()
+    ///     .endpoint("http://localhost:8080/api");
+    /// let client = QrzLogbookClient::with_transport("ABCD-1234-EF56-7890", user_agent, transport).unwrap();
+    /// ```
+    pub fn with_transport(
+        api_key: impl Into<ApiKey>,
+        user_agent: impl Into<String>,
+        transport: impl Transport + 'static,
+    ) -> QrzLogbookResult<Self> {
+        let api_key = api_key.into().normalize()?;
+        let user_agent = user_agent.into();
 
-        let client = Client::builder().user_agent(&user_agent).build()?;
+        validate_user_agent(&user_agent)?;
 
         Ok(Self {
-            client,
+            transport: Arc::new(transport),
+            metrics: None,
+            middleware: Vec::new(),
+            callsign_policy: CallsignPolicy::default(),
+            time_precision: TimePrecision::default(),
+            owner: Mutex::new(None),
             api_key,
             user_agent,
         })
     }
 
+    /// Report request counters and latencies to a metrics hook
+    ///
+    /// See [`metrics`](crate::metrics) for the metric names and labels.
+    pub fn with_metrics(mut self, metrics: impl MetricsHook + 'static) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// Add a middleware that runs around every request
+    ///
+    /// See [`middleware`](crate::middleware) for the order hooks run in.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Check station callsigns against the logbook owner before inserting
+    ///
+    /// The owner is looked up with STATUS on the first insert and cached,
+    /// unless it was set with [`with_logbook_owner`](Self::with_logbook_owner).
+    pub fn with_callsign_policy(mut self, policy: CallsignPolicy) -> Self {
+        self.callsign_policy = policy;
+        self
+    }
+
+    /// Precision of `time_on` and `time_off` in uploaded QSOs
+    ///
+    /// Defaults to [`TimePrecision::Auto`], which only sends seconds when
+    /// they are non-zero.
+    pub fn with_time_precision(mut self, precision: TimePrecision) -> Self {
+        self.time_precision = precision;
+        self
+    }
+
+    /// Set the logbook owner callsign instead of looking it up
+    pub fn with_logbook_owner(self, owner: impl Into<String>) -> Self {
+        *self.owner.lock().unwrap() = Some(owner.into().trim().to_uppercase());
+        self
+    }
+
+    /// Callsign of the logbook owner, looked up with STATUS and cached
+    pub async fn logbook_owner(&self) -> QrzLogbookResult<String> {
+        if let Some(owner) = self.owner.lock().unwrap().clone() {
+            return Ok(owner);
+        }
+
+        let owner = self.verify_key().await?.callsign;
+        *self.owner.lock().unwrap() = Some(owner.clone());
+        Ok(owner)
+    }
+
     /// Insert a single QSO record into the logbook
     ///
     /// # Arguments
@@ -79,7 +161,7 @@ impl QrzLogbookClient {
     /// use chrono::{NaiveDate, NaiveTime};
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let client = QrzLogbookClient::new("YOUR-API-KEY", "MyApp/1.0.0 (YOURCALL)")?;
+    /// let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
     ///
     /// let qso = QsoRecord::builder()
     ///     .call("W1AW")
@@ -100,10 +182,11 @@ impl QrzLogbookClient {
         qso: &QsoRecord,
         replace: bool,
     ) -> QrzLogbookResult<InsertResponse> {
-        let adif = AdifParser::to_adif(qso);
+        let qso = self.check_station_callsign(qso).await?;
+        let adif = AdifParser::to_adif_with_precision(&qso, self.time_precision);
 
         let mut params = vec![
-            ("KEY", self.api_key.as_str()),
+            ("KEY", self.api_key.expose()),
             ("ACTION", "INSERT"),
             ("ADIF", &adif),
         ];
@@ -116,6 +199,132 @@ impl QrzLogbookClient {
         self.parse_insert_response(response)
     }
 
+    /// Update a QSO record with a field-level patch
+    ///
+    /// Fetches the current record and applies the patch. QRZ matches records
+    /// by callsign, date, time on, band and mode:
+    ///
+    /// - If the patch leaves those alone, the record is uploaded with REPLACE.
+    ///   QRZ doesn't allow duplicates, so only the fetched record can match.
+    ///   The logid QRZ reports is still checked, but by then the overwrite has
+    ///   happened; a mismatch is returned as an error.
+    /// - If the patch changes one of them, the record is inserted without
+    ///   REPLACE, which fails instead of overwriting if another record already
+    ///   matches.
+    ///
+    /// Whenever QRZ creates a new record instead of replacing the old one, the
+    /// old record is deleted. If that delete fails the error is returned in
+    /// [`UpdateResponse::delete_error`] and the logbook holds both records.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::QsoPatch;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let patch = QsoPatch::new().set("name", "Hiram").clear("comment");
+    /// let result = client.update_qso(12345, &patch).await?;
+    /// assert!(result.replaced);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_qso(
+        &self,
+        logid: u64,
+        patch: &QsoPatch,
+    ) -> QrzLogbookResult<UpdateResponse> {
+        let mut current = self
+            .fetch_qsos(&FetchOptions::new().logids([logid]))
+            .await?
+            .qsos;
+        if current.len() != 1 {
+            return Err(QrzLogbookError::api_error(format!(
+                "QSO {} not found",
+                logid
+            )));
+        }
+        let current = current.remove(0);
+
+        let mut qso = patch.apply(&current)?;
+        qso.additional_fields
+            .retain(|name, _| !name.starts_with("app_qrzlog_"));
+
+        let same_key = qso.match_key() == current.match_key();
+        let response = self.insert_qso(&qso, same_key).await?;
+        if response.replaced && response.logid != logid {
+            return Err(QrzLogbookError::api_error(format!(
+                "REPLACE updated QSO {} instead of {}",
+                response.logid, logid
+            )));
+        }
+        if response.replaced || response.logid == logid {
+            return Ok(UpdateResponse {
+                logid: response.logid,
+                previous_logid: logid,
+                replaced: response.replaced,
+                qso,
+                delete_error: None,
+            });
+        }
+
+        // A new record was created, so the old one has to go
+        let delete_error = self.delete_qsos(vec![logid]).await.err();
+        Ok(UpdateResponse {
+            logid: response.logid,
+            previous_logid: logid,
+            replaced: false,
+            qso,
+            delete_error,
+        })
+    }
+
+    /// Insert a batch of QSO records
+    ///
+    /// Every record is checked against the callsign policy and attempted on
+    /// its own; the results are returned in input order.
+    pub async fn insert_qsos(
+        &self,
+        qsos: &[QsoRecord],
+        replace: bool,
+    ) -> Vec<QrzLogbookResult<InsertResponse>> {
+        let mut results = Vec::with_capacity(qsos.len());
+        for qso in qsos {
+            results.push(self.insert_qso(qso, replace).await);
+        }
+        results
+    }
+
+    /// Apply the callsign policy to a QSO about to be inserted
+    async fn check_station_callsign<'a>(
+        &self,
+        qso: &'a QsoRecord,
+    ) -> QrzLogbookResult<Cow<'a, QsoRecord>> {
+        if self.callsign_policy == CallsignPolicy::Allow {
+            return Ok(Cow::Borrowed(qso));
+        }
+
+        let owner = self.logbook_owner().await?;
+        let station = qso.station_callsign.trim();
+        if station.eq_ignore_ascii_case(&owner) {
+            return Ok(Cow::Borrowed(qso));
+        }
+
+        match self.callsign_policy {
+            CallsignPolicy::Rewrite if base_callsign(station).eq_ignore_ascii_case(&owner) => {
+                let mut rewritten = qso.clone();
+                rewritten
+                    .operator
+                    .get_or_insert_with(|| station.to_uppercase());
+                rewritten.station_callsign = owner;
+                Ok(Cow::Owned(rewritten))
+            }
+            _ => Err(QrzLogbookError::CallsignMismatch {
+                station_callsign: station.to_string(),
+                owner,
+            }),
+        }
+    }
+
     /// Delete one or more QSO records from the logbook
     ///
     /// # Arguments
@@ -124,7 +333,7 @@ impl QrzLogbookClient {
     /// # Example
     /// ```rust,no_run
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let client = qrz_logbook_api::QrzLogbookClient::new("key", "agent")?;
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
     /// let result = client.delete_qsos(vec![12345, 12346]).await?;
     /// println!("Deleted {} QSOs", result.deleted_count);
     /// # Ok(())
@@ -142,7 +351,7 @@ impl QrzLogbookClient {
             .join(",");
 
         let params = vec![
-            ("KEY", self.api_key.as_str()),
+            ("KEY", self.api_key.expose()),
             ("ACTION", "DELETE"),
             ("LOGIDS", &logids_str),
         ];
@@ -151,12 +360,86 @@ impl QrzLogbookClient {
         self.parse_delete_response(response)
     }
 
+    /// Delete all QSOs matching the fetch options
+    ///
+    /// The matching logids are resolved with
+    /// [`fetch_logids`](Self::fetch_logids) and deleted in chunks of
+    /// `chunk_size`. A dry run returns the logids and a confirmation token
+    /// without deleting anything. Deleting more than `confirm_above` QSOs
+    /// fails with [`QrzLogbookError::ConfirmationRequired`] unless the token
+    /// for exactly the same set of logids is passed with
+    /// [`DeleteOptions::confirm`].
+    ///
+    /// A failed chunk doesn't stop the others; it is listed in
+    /// [`DeleteReport::failed_chunks`] with the reason.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::{DeleteOptions, FetchOptions};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let options = FetchOptions::new().call("N0CALL");
+    /// let preview = client.delete_matching(&options, &DeleteOptions::dry_run()).await?;
+    /// println!("Would delete {} QSOs", preview.logids.len());
+    ///
+    /// let confirm = DeleteOptions::new().confirm(preview.confirmation_token);
+    /// let report = client.delete_matching(&options, &confirm).await?;
+    /// println!("Deleted {} QSOs", report.deleted_count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_matching(
+        &self,
+        options: &FetchOptions,
+        delete: &DeleteOptions,
+    ) -> QrzLogbookResult<DeleteReport> {
+        if delete.chunk_size == 0 {
+            return Err(QrzLogbookError::invalid_params(
+                "Delete chunk size must be at least 1",
+            ));
+        }
+
+        let logids = self.fetch_logids(options).await?;
+        let mut report = DeleteReport {
+            confirmation_token: confirmation_token(&logids),
+            logids,
+            dry_run: delete.dry_run,
+            deleted_count: 0,
+            not_found_logids: Vec::new(),
+            failed_chunks: Vec::new(),
+        };
+        if delete.dry_run || report.logids.is_empty() {
+            return Ok(report);
+        }
+
+        if report.logids.len() > delete.confirm_above
+            && delete.confirmation_token.as_deref() != Some(&report.confirmation_token)
+        {
+            return Err(QrzLogbookError::ConfirmationRequired {
+                count: report.logids.len(),
+                token: report.confirmation_token,
+            });
+        }
+
+        for chunk in report.logids.chunks(delete.chunk_size) {
+            match self.delete_qsos(chunk.to_vec()).await {
+                Ok(response) => {
+                    report.deleted_count += response.deleted_count;
+                    report.not_found_logids.extend(response.not_found_logids);
+                }
+                Err(e) => report.failed_chunks.push((chunk.to_vec(), e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
     /// Get status information about the logbook
     ///
     /// # Example
     /// ```rust,no_run
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let client = qrz_logbook_api::QrzLogbookClient::new("key", "agent")?;
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
     /// let status = client.get_status().await?;
     /// for (key, value) in &status.data {
     ///     println!("{}: {}", key, value);
@@ -165,12 +448,62 @@ impl QrzLogbookClient {
     /// # }
     /// ```
     pub async fn get_status(&self) -> QrzLogbookResult<StatusResponse> {
-        let params = vec![("KEY", self.api_key.as_str()), ("ACTION", "STATUS")];
+        let params = vec![("KEY", self.api_key.expose()), ("ACTION", "STATUS")];
 
         let response = self.make_request(params).await?;
         self.parse_status_response(response)
     }
 
+    /// Check the API key against QRZ and return the logbook it belongs to
+    ///
+    /// Fails with [`QrzLogbookError::KeyRejected`] when QRZ answers STATUS
+    /// with `RESULT=FAIL` and with [`QrzLogbookError::Auth`] when the key lacks the
+    /// privileges to use the logbook API. Transport errors and unexpected
+    /// responses are returned unchanged.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::{QrzLogbookClient, QrzLogbookError};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
+    ///
+    /// match client.verify_key().await {
+    ///     Ok(info) => println!("Key belongs to {}", info.callsign),
+    ///     Err(QrzLogbookError::KeyRejected) => println!("Unknown key"),
+    ///     Err(QrzLogbookError::Auth) => println!("Key lacks logbook privileges"),
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_key(&self) -> QrzLogbookResult<KeyInfo> {
+        let params = vec![("KEY", self.api_key.expose()), ("ACTION", "STATUS")];
+        let response = self.make_request(params).await?;
+
+        // The key is the only input to STATUS, so RESULT=FAIL means QRZ
+        // rejected the key, whatever the wording of REASON
+        if response_field(&response, "RESULT") == Some("FAIL") {
+            return Err(QrzLogbookError::KeyRejected);
+        }
+        let status = self.parse_status_response(response)?;
+        let field = |names: &[&str]| {
+            status
+                .data
+                .iter()
+                .find(|(key, _)| names.iter().any(|n| key.eq_ignore_ascii_case(n)))
+                .map(|(_, value)| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let callsign = field(&["OWNER", "CALLSIGN"])
+            .ok_or_else(|| QrzLogbookError::api_error("STATUS response has no owner callsign"))?;
+        Ok(KeyInfo {
+            callsign: callsign.to_uppercase(),
+            book_id: field(&["BOOKID", "BOOK_ID"]),
+        })
+    }
+
     /// Fetch QSO records from the logbook with optional filtering
     ///
     /// # Arguments
@@ -181,7 +514,7 @@ impl QrzLogbookClient {
     /// use qrz_logbook_api::FetchOptions;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let client = qrz_logbook_api::QrzLogbookClient::new("key", "agent")?;
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
     /// // Fetch all QSOs
     /// let all_qsos = client.fetch_qsos(&FetchOptions::all()).await?;
     ///
@@ -195,9 +528,10 @@ impl QrzLogbookClient {
     /// # }
     /// ```
     pub async fn fetch_qsos(&self, options: &FetchOptions) -> QrzLogbookResult<FetchResponse> {
+        options.validate()?;
         let option_string = options.to_option_string();
 
-        let mut params = vec![("KEY", self.api_key.as_str()), ("ACTION", "FETCH")];
+        let mut params = vec![("KEY", self.api_key.expose()), ("ACTION", "FETCH")];
 
         if !option_string.is_empty() {
             params.push(("OPTION", &option_string));
@@ -220,7 +554,7 @@ impl QrzLogbookClient {
     /// use qrz_logbook_api::FetchOptions;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let client = qrz_logbook_api::QrzLogbookClient::new("key", "agent")?;
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
     /// let all_qsos = client.fetch_all_qsos(&FetchOptions::new().band("20m")).await?;
     /// println!("Retrieved {} QSOs total", all_qsos.len());
     /// # Ok(())
@@ -228,6 +562,11 @@ impl QrzLogbookClient {
     /// ```
     /// This method will continue fetching QSOs until no more records are available or the API returns an empty page.
     pub async fn fetch_all_qsos(&self, options: &FetchOptions) -> QrzLogbookResult<Vec<QsoRecord>> {
+        // Specific logids are fetched in one request, without paging
+        if !options.logids.is_empty() {
+            return Ok(self.fetch_qsos(options).await?.qsos);
+        }
+
         let mut all_qsos = Vec::new();
         let mut after_logid = 0u64;
         let page_size = 250u32;
@@ -263,24 +602,248 @@ impl QrzLogbookClient {
         Ok(all_qsos)
     }
 
+    /// Fetch the logids of all QSOs matching the options, without ADIF
+    ///
+    /// Pages through the logbook with `TYPE:LOGIDS`, which is much cheaper
+    /// than [`fetch_all_qsos`](Self::fetch_all_qsos) when only the set of
+    /// records matters. The logids are returned in ascending order.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::FetchOptions;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let logids = client.fetch_logids(&FetchOptions::all()).await?;
+    /// println!("Logbook holds {} QSOs", logids.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_logids(&self, options: &FetchOptions) -> QrzLogbookResult<Vec<u64>> {
+        let mut all_logids = Vec::new();
+        let mut after_logid = 0u64;
+        let page_size = 250u32;
+
+        loop {
+            let mut page_options = options.clone();
+            page_options.fetch_type = Some(FetchType::Logids);
+            page_options.max = Some(page_size);
+            page_options.after_logid = if after_logid > 0 {
+                Some(after_logid)
+            } else {
+                None
+            };
+
+            let response = self.fetch_qsos(&page_options).await?;
+
+            match response.logids.iter().max() {
+                Some(max_logid) => after_logid = max_logid + 1,
+                None => break,
+            }
+
+            let page_len = response.logids.len();
+            all_logids.extend(response.logids);
+
+            if page_len < page_size as usize {
+                break;
+            }
+        }
+
+        all_logids.sort_unstable();
+        all_logids.dedup();
+        Ok(all_logids)
+    }
+
+    /// Compare a local set of logids with the logbook
+    ///
+    /// Returns the logids added on the server since the local copy was made
+    /// and those deleted from it, using [`fetch_logids`](Self::fetch_logids).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let known = vec![1001, 1002, 1003];
+    /// let diff = client.reconcile_logids(known).await?;
+    /// for logid in &diff.removed {
+    ///     println!("Deleted on QRZ: {}", logid);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reconcile_logids(
+        &self,
+        local: impl IntoIterator<Item = u64>,
+    ) -> QrzLogbookResult<LogidDiff> {
+        let remote = self.fetch_logids(&FetchOptions::all()).await?;
+        Ok(sync::reconcile(local, remote))
+    }
+
+    /// Fetch all QSOs modified or confirmed since a timestamp
+    ///
+    /// Uses the `MODSINCE` fetch option with automatic paging. The server
+    /// filters by date, and may stamp changes in its local time rather than
+    /// UTC, so the request starts one day before `since`. Records changed in
+    /// that window are returned again; callers reconcile them by logid.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use chrono::{Duration, Utc};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let changed = client.fetch_modified_since(Utc::now() - Duration::hours(1)).await?;
+    /// println!("{} QSOs changed", changed.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_modified_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> QrzLogbookResult<Vec<QsoRecord>> {
+        let date = since.date_naive();
+        let options = FetchOptions::new().modified_since(date.pred_opt().unwrap_or(date));
+        self.fetch_all_qsos(&options).await
+    }
+
+    /// Fetch QSOs changed since the last call and advance the mark
+    ///
+    /// Fetches the whole logbook when the mark is unset. The mark is only
+    /// updated after a successful download, to the time the download started.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::sync::HighWaterMark;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let mut mark = HighWaterMark::load("qrz-sync.json")?;
+    /// let changed = client.fetch_incremental(&mut mark).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_incremental(
+        &self,
+        mark: &mut HighWaterMark,
+    ) -> QrzLogbookResult<Vec<QsoRecord>> {
+        let started = Utc::now();
+        let qsos = match mark.since() {
+            Some(since) => self.fetch_modified_since(since).await?,
+            None => self.fetch_all_qsos(&FetchOptions::all()).await?,
+        };
+
+        mark.set(started)?;
+        Ok(qsos)
+    }
+
+    /// Fetch all QSOs matching a client-side filter
+    ///
+    /// Conditions the FETCH action supports are sent to the server (see
+    /// [`QsoFilter::plan`]); the rest are applied to the fetched records.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use qrz_logbook_api::query::QsoFilter;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+    /// let filter: QsoFilter = "band=20m and mode in (CW,FT8)".parse()?;
+    /// let qsos = client.fetch_matching(&filter).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_matching(&self, filter: &QsoFilter) -> QrzLogbookResult<Vec<QsoRecord>> {
+        let plan = filter.plan();
+        let mut qsos = self.fetch_all_qsos(&plan.options).await?;
+
+        if let Some(residual) = plan.residual {
+            qsos.retain(|qso| residual.matches(qso));
+        }
+
+        Ok(qsos)
+    }
+
     async fn make_request(&self, params: Vec<(&str, &str)>) -> QrzLogbookResult<String> {
-        let response = self.client.post(API_ENDPOINT).form(&params).send().await?;
+        let request = TransportRequest::new(
+            params
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        );
+        let action = request.param("ACTION").unwrap_or_default().to_string();
+        let span = tracing::info_span!(
+            "qrz_request",
+            action = %action,
+            records = field::Empty,
+            logid = field::Empty,
+            result = field::Empty,
+            latency_ms = field::Empty,
+        );
 
-        if !response.status().is_success() {
-            return Err(QrzLogbookError::Http(
-                response.error_for_status().unwrap_err(),
-            ));
+        async move {
+            let started = Instant::now();
+            let response = self.send_through_middleware(request).await;
+            let latency = started.elapsed();
+
+            let span = Span::current();
+            span.record("latency_ms", latency.as_millis() as u64);
+            let result = match &response {
+                Ok(body) => {
+                    if let Some(count) = response_field(body, "COUNT") {
+                        span.record("records", count);
+                    }
+                    if let Some(logid) = response_field(body, "LOGID") {
+                        span.record("logid", logid);
+                    }
+                    tracing::trace!(body = %body, "received response");
+                    response_field(body, "RESULT").unwrap_or("UNKNOWN")
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "request failed");
+                    "ERROR"
+                }
+            };
+            span.record("result", result);
+            tracing::debug!(result, "request finished");
+
+            if let Some(metrics) = &self.metrics {
+                let labels = [("action", action.as_str()), ("result", result)];
+                metrics.increment_counter(REQUESTS_TOTAL, &labels);
+                metrics.record_histogram(REQUEST_DURATION_SECONDS, latency.as_secs_f64(), &labels);
+            }
+
+            response
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn send_through_middleware(
+        &self,
+        mut request: TransportRequest,
+    ) -> QrzLogbookResult<String> {
+        for middleware in &self.middleware {
+            middleware.before_send(&mut request)?;
         }
 
-        Ok(response.text().await?)
+        tracing::debug!(?request, "sending request");
+        let mut response = self.transport.send(request.clone()).await?;
+
+        for middleware in self.middleware.iter().rev() {
+            middleware.after_receive(&request, &mut response)?;
+        }
+        Ok(response)
     }
 
     /// Parse the response from an INSERT action
+    ///
+    /// `RESULT=REPLACE` is returned instead of `OK` when the REPLACE option
+    /// overwrote an existing duplicate QSO.
     pub fn parse_insert_response(&self, response: String) -> QrzLogbookResult<InsertResponse> {
         let params = self.parse_response_params(&response)?;
 
         match params.get("RESULT").map(|s| s.as_str()) {
-            Some("OK") => {
+            Some("OK") | Some("REPLACE") => {
                 let logid = params
                     .get("LOGID")
                     .ok_or_else(|| QrzLogbookError::api_error("Missing LOGID in response"))?
@@ -293,15 +856,13 @@ impl QrzLogbookClient {
                     .parse()
                     .map_err(|_| QrzLogbookError::api_error("Invalid COUNT format"))?;
 
-                Ok(InsertResponse { logid, count })
-            }
-            Some("FAIL") => {
-                let reason = params
-                    .get("REASON")
-                    .map(|s| s.as_str())
-                    .unwrap_or("Unknown error");
-                Err(QrzLogbookError::api_error(reason))
+                Ok(InsertResponse {
+                    logid,
+                    count,
+                    replaced: params.get("RESULT").map(|s| s.as_str()) == Some("REPLACE"),
+                })
             }
+            Some("FAIL") => Err(fail_error(&params)),
             Some("AUTH") => Err(QrzLogbookError::Auth),
             _ => Err(QrzLogbookError::api_error("Unexpected response format")),
         }
@@ -333,13 +894,7 @@ impl QrzLogbookClient {
                     not_found_logids,
                 })
             }
-            Some("FAIL") => {
-                let reason = params
-                    .get("REASON")
-                    .map(|s| s.as_str())
-                    .unwrap_or("Unknown error");
-                Err(QrzLogbookError::api_error(reason))
-            }
+            Some("FAIL") => Err(fail_error(&params)),
             Some("AUTH") => Err(QrzLogbookError::Auth),
             _ => Err(QrzLogbookError::api_error("Unexpected response format")),
         }
@@ -359,13 +914,7 @@ impl QrzLogbookClient {
 
                 Ok(StatusResponse { data })
             }
-            Some("FAIL") => {
-                let reason = params
-                    .get("REASON")
-                    .map(|s| s.as_str())
-                    .unwrap_or("Unknown error");
-                Err(QrzLogbookError::api_error(reason))
-            }
+            Some("FAIL") => Err(fail_error(&params)),
             Some("AUTH") => Err(QrzLogbookError::Auth),
             _ => Err(QrzLogbookError::api_error("Unexpected response format")),
         }
@@ -403,13 +952,7 @@ impl QrzLogbookClient {
                     qsos,
                 })
             }
-            Some("FAIL") => {
-                let reason = params
-                    .get("REASON")
-                    .map(|s| s.as_str())
-                    .unwrap_or("Unknown error");
-                Err(QrzLogbookError::api_error(reason))
-            }
+            Some("FAIL") => Err(fail_error(&params)),
             Some("AUTH") => Err(QrzLogbookError::Auth),
             _ => Err(QrzLogbookError::api_error("Unexpected response format")),
         }
@@ -451,13 +994,68 @@ impl QrzLogbookClient {
     }
 }
 
+/// Token identifying a set of logids for delete confirmation
+///
+/// Uses 64-bit FNV-1a over the sorted logids rather than std's hasher, whose
+/// algorithm may change between Rust releases, so saved tokens stay valid.
+fn confirmation_token(logids: &[u64]) -> String {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut sorted = logids.to_vec();
+    sorted.sort_unstable();
+    let hash = sorted
+        .iter()
+        .flat_map(|logid| logid.to_le_bytes())
+        .fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+    format!("DELETE-{}-{:016x}", logids.len(), hash)
+}
+
+/// Error for a `RESULT=FAIL` response
+fn fail_error(params: &HashMap<String, String>) -> QrzLogbookError {
+    let reason = params
+        .get("REASON")
+        .map(|s| s.as_str())
+        .unwrap_or("Unknown error");
+    QrzLogbookError::api_error(reason)
+}
+
+/// Raw value of a top-level response field, without decoding the rest of
+/// the (possibly large) body
+fn response_field<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn validate_user_agent(user_agent: &str) -> QrzLogbookResult<()> {
+    if user_agent.is_empty() || user_agent.len() > 128 {
+        return Err(QrzLogbookError::InvalidUserAgent);
+    }
+
+    // Check for generic user agents
+    let lower_ua = user_agent.to_lowercase();
+    if lower_ua.contains("python-requests")
+        || lower_ua.contains("node-fetch")
+        || lower_ua == "curl"
+        || lower_ua == "wget"
+    {
+        return Err(QrzLogbookError::InvalidUserAgent);
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_client_creation() {
-        let client = QrzLogbookClient::new("test-api-key-12345", "TestApp/1.0.0 (N0CALL)");
+        let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestApp/1.0.0 (N0CALL)");
         assert!(client.is_ok());
     }
 
@@ -469,13 +1067,25 @@ mod tests {
 
     #[test]
     fn test_invalid_user_agent() {
-        let client = QrzLogbookClient::new("test-api-key-12345", "python-requests");
+        let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "python-requests");
         assert!(matches!(client, Err(QrzLogbookError::InvalidUserAgent)));
     }
 
+    #[test]
+    fn test_confirmation_token_is_stable() {
+        // Pinned so a toolchain or hasher change can't invalidate saved tokens
+        assert_eq!(confirmation_token(&[1, 2, 3]), "DELETE-3-da2bfb225e0d1f05");
+        assert_eq!(
+            confirmation_token(&[3, 1, 2]),
+            confirmation_token(&[1, 2, 3])
+        );
+        assert_eq!(confirmation_token(&[]), "DELETE-0-cbf29ce484222325");
+    }
+
     #[test]
     fn test_parse_response_params() {
-        let client = QrzLogbookClient::new("test-api-key-12345", "TestApp/1.0.0 (N0CALL)").unwrap();
+        let client =
+            QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestApp/1.0.0 (N0CALL)").unwrap();
         let response = "RESULT=OK&LOGID=12345&COUNT=1";
         let params = client.parse_response_params(response).unwrap();
 
@@ -483,4 +1093,12 @@ mod tests {
         assert_eq!(params.get("LOGID"), Some(&"12345".to_string()));
         assert_eq!(params.get("COUNT"), Some(&"1".to_string()));
     }
+
+    #[test]
+    fn test_response_field() {
+        let body = "RESULT=OK&COUNT=2&LOGIDS=1%2C2&ADIF=%3Ccall%3A4%3EW1AW";
+        assert_eq!(response_field(body, "RESULT"), Some("OK"));
+        assert_eq!(response_field(body, "COUNT"), Some("2"));
+        assert_eq!(response_field(body, "LOGID"), None);
+    }
 }
@@ -0,0 +1,567 @@
+//! CSV import and export with configurable column mapping
+//!
+//! A [`CsvMapping`] maps spreadsheet columns onto ADIF field names. Built-in
+//! fields (`call`, `qso_date`, `time_on`, ...) fill the matching
+//! [`QsoRecord`] fields and any other name ends up in `additional_fields`.
+//! Dates and times are read and written with the mapping's format strings.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::csv::CsvMapping;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mapping = CsvMapping::new()
+//!     .column("Callsign", "call")
+//!     .column("Date", "qso_date")
+//!     .column("UTC", "time_on")
+//!     .column("kHz", "freq_khz")
+//!     .column("Mode", "mode")
+//!     .column("Grid", "gridsquare")
+//!     .date_format("%d/%m/%Y")
+//!     .default_value("station_callsign", "W1AW");
+//!
+//! let import = mapping.read(std::fs::File::open("paper-log.csv")?)?;
+//! for error in &import.errors {
+//!     eprintln!("line {}: {}", error.line, error.message);
+//! }
+//! println!("Imported {} QSOs", import.records.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::{band_for_freq, parse_date, parse_time, AdifParser},
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::QsoRecord,
+};
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+/// Pseudo field holding the frequency in kHz, stored as `freq` in MHz
+const FREQ_KHZ: &str = "freq_khz";
+
+/// One CSV column mapped onto an ADIF field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvColumn {
+    /// Column header, or 1-based column number when the file has no header row
+    pub column: String,
+    /// ADIF field name (e.g. "call", "qso_date", "gridsquare") or "freq_khz"
+    #[serde(deserialize_with = "lowercase")]
+    pub field: String,
+}
+
+/// Column mapping and formats for CSV import and export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvMapping {
+    pub columns: Vec<CsvColumn>,
+    /// chrono format of date columns
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// chrono format of time columns
+    #[serde(default = "default_time_format")]
+    pub time_format: String,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default = "default_has_headers")]
+    pub has_headers: bool,
+    /// ADIF field values used when a row leaves the field empty
+    #[serde(default, deserialize_with = "lowercase_keys")]
+    pub defaults: HashMap<String, String>,
+}
+
+fn lowercase<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    String::deserialize(deserializer).map(|field| field.to_lowercase())
+}
+
+fn lowercase_keys<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, String>, D::Error> {
+    let fields = HashMap::<String, String>::deserialize(deserializer)?;
+    Ok(fields
+        .into_iter()
+        .map(|(field, value)| (field.to_lowercase(), value))
+        .collect())
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_time_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_has_headers() -> bool {
+    true
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            date_format: default_date_format(),
+            time_format: default_time_format(),
+            delimiter: default_delimiter(),
+            has_headers: default_has_headers(),
+            defaults: HashMap::new(),
+        }
+    }
+}
+
+/// Error in a single CSV row
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRowError {
+    /// 1-based line number in the file
+    pub line: u64,
+    pub message: String,
+}
+
+/// Result of a CSV import; rows with errors are skipped
+#[derive(Debug, Clone, Default)]
+pub struct CsvImport {
+    pub records: Vec<QsoRecord>,
+    pub errors: Vec<CsvRowError>,
+}
+
+impl CsvMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a column onto an ADIF field
+    pub fn column(mut self, column: impl Into<String>, field: impl Into<String>) -> Self {
+        self.columns.push(CsvColumn {
+            column: column.into(),
+            field: field.into().to_lowercase(),
+        });
+        self
+    }
+
+    pub fn date_format(mut self, format: impl Into<String>) -> Self {
+        self.date_format = format.into();
+        self
+    }
+
+    pub fn time_format(mut self, format: impl Into<String>) -> Self {
+        self.time_format = format.into();
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Value for an ADIF field when a row leaves it empty (e.g. `station_callsign`)
+    pub fn default_value(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defaults
+            .insert(field.into().to_lowercase(), value.into());
+        self
+    }
+
+    /// Read QSO records from CSV
+    ///
+    /// Rows that fail to convert are reported in [`CsvImport::errors`] with
+    /// their line number. A mapping referring to a missing column is an error.
+    pub fn read(&self, reader: impl Read) -> QrzLogbookResult<CsvImport> {
+        let mut csv = ::csv::ReaderBuilder::new()
+            .delimiter(self.delimiter_byte()?)
+            .has_headers(self.has_headers)
+            .flexible(true)
+            .from_reader(reader);
+
+        let indices = self.column_indices(&mut csv)?;
+        let mut import = CsvImport::default();
+
+        for result in csv.records() {
+            let row = match result {
+                Ok(row) => row,
+                Err(e) => {
+                    if e.is_io_error() {
+                        return Err(std::io::Error::from(e).into());
+                    }
+                    import.errors.push(CsvRowError {
+                        line: e.position().map(|p| p.line()).unwrap_or_default(),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let line = row.position().map(|p| p.line()).unwrap_or_default();
+            if row.iter().all(|value| value.trim().is_empty()) {
+                continue;
+            }
+
+            let values = indices
+                .iter()
+                .map(|(index, field)| (field.as_str(), row.get(*index).unwrap_or_default()));
+            match self.row_to_qso(values) {
+                Ok(qso) => import.records.push(qso),
+                Err(e) => import.errors.push(CsvRowError {
+                    line,
+                    message: match e {
+                        QrzLogbookError::AdifParse(msg) => msg,
+                        e => e.to_string(),
+                    },
+                }),
+            }
+        }
+
+        Ok(import)
+    }
+
+    /// Write QSO records as CSV using the mapped columns
+    pub fn write(&self, qsos: &[QsoRecord], writer: impl Write) -> QrzLogbookResult<()> {
+        let delimiter = self.delimiter_byte()?;
+        self.check_formats()?;
+        let mut csv = ::csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(writer);
+
+        if self.has_headers {
+            csv.write_record(self.columns.iter().map(|c| c.column.as_str()))
+                .map_err(std::io::Error::from)?;
+        }
+
+        for qso in qsos {
+            let fields: HashMap<String, String> = AdifParser::to_fields(qso).into_iter().collect();
+            let row = self
+                .columns
+                .iter()
+                .map(|c| self.format_value(&c.field, &fields))
+                .collect::<QrzLogbookResult<Vec<_>>>()?;
+            csv.write_record(&row).map_err(std::io::Error::from)?;
+        }
+
+        csv.flush()?;
+        Ok(())
+    }
+
+    fn delimiter_byte(&self) -> QrzLogbookResult<u8> {
+        u8::try_from(self.delimiter).map_err(|_| {
+            QrzLogbookError::invalid_params("CSV delimiter must be an ASCII character")
+        })
+    }
+
+    /// Fail early on date or time formats chrono can't render
+    fn check_formats(&self) -> QrzLogbookResult<()> {
+        render(NaiveDate::MIN.format(&self.date_format), "date_format")?;
+        render(NaiveTime::MIN.format(&self.time_format), "time_format")?;
+        Ok(())
+    }
+
+    fn column_indices<R: Read>(
+        &self,
+        csv: &mut ::csv::Reader<R>,
+    ) -> QrzLogbookResult<Vec<(usize, String)>> {
+        let headers = if self.has_headers {
+            Some(csv.headers().map_err(std::io::Error::from)?.clone())
+        } else {
+            None
+        };
+
+        self.columns
+            .iter()
+            .map(|c| {
+                let index = match headers {
+                    Some(ref headers) => headers
+                        .iter()
+                        .position(|h| h.trim().eq_ignore_ascii_case(c.column.trim())),
+                    None => c
+                        .column
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(1)),
+                };
+                index.map(|index| (index, c.field.clone())).ok_or_else(|| {
+                    QrzLogbookError::invalid_params(format!("CSV column {} not found", c.column))
+                })
+            })
+            .collect()
+    }
+
+    fn row_to_qso<'a>(
+        &self,
+        values: impl Iterator<Item = (&'a str, &'a str)>,
+    ) -> QrzLogbookResult<QsoRecord> {
+        let mut fields = HashMap::new();
+
+        for (field, value) in values {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            let (field, value) = match field {
+                "qso_date" | "qso_date_off" => {
+                    let date =
+                        NaiveDate::parse_from_str(value, &self.date_format).map_err(|_| {
+                            QrzLogbookError::adif_parse(format!("Invalid {}: {}", field, value))
+                        })?;
+                    (field, date.format("%Y%m%d").to_string())
+                }
+                "time_on" | "time_off" => {
+                    let time =
+                        NaiveTime::parse_from_str(value, &self.time_format).map_err(|_| {
+                            QrzLogbookError::adif_parse(format!("Invalid {}: {}", field, value))
+                        })?;
+                    (field, time.format("%H%M%S").to_string())
+                }
+                FREQ_KHZ => {
+                    let khz: f64 = value.parse().map_err(|_| {
+                        QrzLogbookError::adif_parse(format!("Invalid frequency: {}", value))
+                    })?;
+                    ("freq", (khz / 1000.0).to_string())
+                }
+                "call" | "station_callsign" | "operator" => (field, value.to_uppercase()),
+                _ => (field, value.to_string()),
+            };
+            fields.insert(field.to_string(), value);
+        }
+
+        for (field, value) in &self.defaults {
+            fields.entry(field.clone()).or_insert_with(|| value.clone());
+        }
+
+        // Paper logs often record the frequency but not the band
+        if !fields.contains_key("band") {
+            if let Some(band) = fields
+                .get("freq")
+                .and_then(|f| f.parse().ok())
+                .and_then(band_for_freq)
+            {
+                fields.insert("band".to_string(), band.to_string());
+            }
+        }
+
+        AdifParser::fields_to_qso(fields)
+    }
+
+    fn format_value(
+        &self,
+        field: &str,
+        fields: &HashMap<String, String>,
+    ) -> QrzLogbookResult<String> {
+        let value = match field {
+            FREQ_KHZ => fields
+                .get("freq")
+                .and_then(|f| f.parse::<f64>().ok())
+                .map(|mhz| {
+                    let khz = format!("{:.3}", mhz * 1000.0);
+                    khz.trim_end_matches('0').trim_end_matches('.').to_string()
+                }),
+            "qso_date" | "qso_date_off" => fields
+                .get(field)
+                .map(|d| parse_date(d))
+                .transpose()?
+                .map(|d| render(d.format(&self.date_format), "date_format"))
+                .transpose()?,
+            "time_on" | "time_off" => fields
+                .get(field)
+                .map(|t| parse_time(t))
+                .transpose()?
+                .map(|t| render(t.format(&self.time_format), "time_format"))
+                .transpose()?,
+            _ => fields.get(field).cloned(),
+        };
+
+        Ok(value.unwrap_or_default())
+    }
+}
+
+/// Render a chrono format without the panic `to_string` raises when the
+/// format asks for fields the value doesn't have
+fn render(value: impl std::fmt::Display, setting: &str) -> QrzLogbookResult<String> {
+    use std::fmt::Write as _;
+
+    let mut rendered = String::new();
+    write!(rendered, "{}", value)
+        .map_err(|_| QrzLogbookError::invalid_params(format!("Invalid CSV {}", setting)))?;
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper_log_mapping() -> CsvMapping {
+        CsvMapping::new()
+            .column("Callsign", "call")
+            .column("Date", "qso_date")
+            .column("UTC", "time_on")
+            .column("kHz", "freq_khz")
+            .column("Mode", "mode")
+            .column("RST S", "rst_sent")
+            .column("RST R", "rst_rcvd")
+            .column("Grid", "gridsquare")
+            .date_format("%d/%m/%Y")
+            .default_value("station_callsign", "W1AW")
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let csv = "Callsign,Date,UTC,kHz,Mode,RST S,RST R,Grid,Notes
+ja1xy,15/03/2024,12:30,14025,CW,599,579,PM95,first one
+DL1AB,15/03/2024,12:45,7011.5,CW,599,599,,
+";
+        let import = paper_log_mapping().read(csv.as_bytes()).unwrap();
+        assert!(import.errors.is_empty(), "{:?}", import.errors);
+        assert_eq!(import.records.len(), 2);
+
+        let ja1xy = &import.records[0];
+        assert_eq!(ja1xy.call, "JA1XY");
+        assert_eq!(ja1xy.station_callsign, "W1AW");
+        assert_eq!(
+            ja1xy.qso_date,
+            NaiveDate::from_ymd_opt(2024, 3, 15).unwrap()
+        );
+        assert_eq!(ja1xy.time_on, NaiveTime::from_hms_opt(12, 30, 0).unwrap());
+        assert_eq!(ja1xy.freq, Some(14.025));
+        assert_eq!(ja1xy.band, "20m");
+        assert_eq!(ja1xy.gridsquare.as_deref(), Some("PM95"));
+
+        let dl1ab = &import.records[1];
+        assert_eq!(dl1ab.band, "40m");
+        assert_eq!(dl1ab.gridsquare, None);
+    }
+
+    #[test]
+    fn test_read_csv_row_errors() {
+        let csv = "Callsign,Date,UTC,kHz,Mode,RST S,RST R,Grid
+JA1XY,15/03/2024,12:30,14025,CW,599,579,PM95
+DL1AB,2024-03-15,12:45,7011,CW,599,599,
+K2XYZ,15/03/2024,12:50,abc,CW,599,599,
+N0CALL,15/03/2024,12:55,,CW,599,599,
+";
+        let import = paper_log_mapping().read(csv.as_bytes()).unwrap();
+        assert_eq!(import.records.len(), 1);
+        assert_eq!(import.errors.len(), 3);
+        assert_eq!(import.errors[0].line, 3);
+        assert!(import.errors[0].message.contains("qso_date"));
+        assert_eq!(import.errors[1].line, 4);
+        assert!(import.errors[1].message.contains("frequency"));
+        assert_eq!(import.errors[2].line, 5);
+        assert!(import.errors[2].message.contains("band"));
+
+        let mut bytes = b"Callsign,Date,UTC,kHz,Mode,RST S,RST R,Grid\n".to_vec();
+        bytes.extend_from_slice(b"JA1XY,15/03/2024,12:30,14025,CW,599,579,\xff\n");
+        let import = paper_log_mapping().read(bytes.as_slice()).unwrap();
+        assert_eq!(import.errors[0].line, 2);
+        assert!(import.errors[0].message.contains("UTF-8"));
+    }
+
+    #[test]
+    fn test_read_csv_without_headers() {
+        let csv = "W1AW;K1ABC;20240115;1430;20m;SSB\n";
+        let mapping = CsvMapping::new()
+            .column("1", "call")
+            .column("2", "station_callsign")
+            .column("3", "qso_date")
+            .column("4", "time_on")
+            .column("5", "band")
+            .column("6", "mode")
+            .date_format("%Y%m%d")
+            .time_format("%H%M")
+            .delimiter(';')
+            .has_headers(false);
+
+        let import = mapping.read(csv.as_bytes()).unwrap();
+        assert_eq!(import.records.len(), 1);
+        assert_eq!(import.records[0].call, "W1AW");
+    }
+
+    #[test]
+    fn test_read_csv_missing_column() {
+        let mapping = CsvMapping::new().column("Frequency", "freq");
+        let result = mapping.read("Callsign\nW1AW\n".as_bytes());
+        assert!(matches!(result, Err(QrzLogbookError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let qso = QsoRecord::builder()
+            .call("JA1XY")
+            .station_callsign("W1AW")
+            .date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(12, 30, 0).unwrap())
+            .band("20m")
+            .mode("CW")
+            .freq(14.0255)
+            .rst_sent("599")
+            .gridsquare("PM95")
+            .build();
+
+        let mut output = Vec::new();
+        paper_log_mapping().write(&[qso], &mut output).unwrap();
+        let csv = String::from_utf8(output).unwrap();
+        assert_eq!(
+            csv,
+            "Callsign,Date,UTC,kHz,Mode,RST S,RST R,Grid\nJA1XY,15/03/2024,12:30,14025.5,CW,599,,PM95\n"
+        );
+
+        let import = paper_log_mapping().read(csv.as_bytes()).unwrap();
+        assert_eq!(import.records[0].freq, Some(14.0255));
+        assert_eq!(import.records[0].rst_rcvd, None);
+    }
+
+    #[test]
+    fn test_write_invalid_format() {
+        let qso = QsoRecord::builder()
+            .call("JA1XY")
+            .time_on(NaiveTime::from_hms_opt(12, 30, 0).unwrap())
+            .build();
+        let mapping = CsvMapping::new()
+            .column("Time", "time_on")
+            .time_format("%d");
+
+        let result = mapping.write(&[qso], Vec::new());
+        assert!(matches!(result, Err(QrzLogbookError::InvalidParams(_))));
+        assert!(matches!(
+            CsvMapping::new().date_format("%H").write(&[], Vec::new()),
+            Err(QrzLogbookError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_mapping_from_json() {
+        let mapping: CsvMapping = serde_json::from_str(
+            r#"{
+                "columns": [
+                    {"column": "Call", "field": "CALL"},
+                    {"column": "Date", "field": "QSO_Date"},
+                    {"column": "UTC", "field": "time_on"},
+                    {"column": "Band", "field": "band"},
+                    {"column": "Mode", "field": "mode"}
+                ],
+                "date_format": "%m/%d/%Y",
+                "defaults": {"STATION_CALLSIGN": "W1AW"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(mapping.date_format, "%m/%d/%Y");
+        assert_eq!(mapping.time_format, "%H:%M");
+        assert!(mapping.has_headers);
+        assert_eq!(mapping.columns[0].field, "call");
+
+        let csv = "Call,Date,UTC,Band,Mode\nk1abc,01/15/2024,14:30,20m,SSB\n";
+        let import = mapping.read(csv.as_bytes()).unwrap();
+        assert!(import.errors.is_empty(), "{:?}", import.errors);
+        assert_eq!(import.records[0].call, "K1ABC");
+        assert_eq!(import.records[0].station_callsign, "W1AW");
+    }
+}
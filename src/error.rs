@@ -19,9 +19,13 @@ pub enum QrzLogbookError {
     Auth,
 
     /// Invalid API key format
-    #[error("Invalid API key format")]
+    #[error("Invalid API key format: expected XXXX-XXXX-XXXX-XXXX")]
     InvalidKey,
 
+    /// QRZ rejected the API key (reported by `verify_key`)
+    #[error("API key was rejected by QRZ")]
+    KeyRejected,
+
     /// Invalid user agent format
     #[error("Invalid user agent: must be 128 characters or less and identifiable")]
     InvalidUserAgent,
@@ -41,6 +45,33 @@ pub enum QrzLogbookError {
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+
+    /// Invalid or unreadable configuration
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Malformed message or log file from an external logging program
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    /// QSO station callsign doesn't match the logbook owner
+    #[error("Station callsign {station_callsign} does not match logbook owner {owner}")]
+    CallsignMismatch {
+        station_callsign: String,
+        owner: String,
+    },
+
+    /// A bulk delete needs an explicit confirmation token
+    #[error("Deleting {count} QSOs requires a confirmation token")]
+    ConfirmationRequired { count: usize, token: String },
+
+    /// No logbook profile matches the station callsign
+    #[error("No logbook profile configured for station callsign {0}")]
+    NoProfile(String),
 }
 
 impl QrzLogbookError {
@@ -57,4 +88,12 @@ impl QrzLogbookError {
     pub fn invalid_params(msg: impl Into<String>) -> Self {
         Self::InvalidParams(msg.into())
     }
+
+    pub fn protocol(msg: impl Into<String>) -> Self {
+        Self::Protocol(msg.into())
+    }
+
+    pub fn config(msg: impl Into<String>) -> Self {
+        Self::Config(msg.into())
+    }
 }
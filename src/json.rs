@@ -0,0 +1,271 @@
+//! Flat ADIF-named JSON for QSO records
+//!
+//! The derived `Serialize` implementation of [`QsoRecord`] nests
+//! `additional_fields` and uses chrono's ISO formats. This module offers a
+//! flat object keyed by uppercase ADIF field names with values in ADIF
+//! formats instead:
+//!
+//! ```json
+//! {"CALL":"W1AW","STATION_CALLSIGN":"K1ABC","QSO_DATE":"20240115","TIME_ON":"1430","BAND":"20m","MODE":"SSB"}
+//! ```
+//!
+//! Use [`AdifJson`] as a wrapper, `#[serde(with = "qrz_logbook_api::json")]`
+//! on a `QsoRecord` field, or the NDJSON helpers for streams of records.
+//!
+//! # Example
+//! ```rust
+//! use qrz_logbook_api::json::{self, NdjsonReader};
+//! use qrz_logbook_api::QsoRecord;
+//! use chrono::{NaiveDate, NaiveTime};
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let qso = QsoRecord::builder()
+//!     .call("W1AW")
+//!     .station_callsign("K1ABC")
+//!     .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+//!     .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+//!     .band("20m")
+//!     .mode("SSB")
+//!     .build();
+//!
+//! let mut ndjson = Vec::new();
+//! json::write_ndjson(&mut ndjson, &[qso])?;
+//!
+//! for qso in NdjsonReader::new(ndjson.as_slice()) {
+//!     println!("{}", qso?.call);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{adif::AdifParser, error::QrzLogbookResult, models::QsoRecord};
+use serde::{
+    de::{self, Deserializer},
+    ser::{SerializeMap, Serializer},
+    Deserialize, Serialize,
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+/// Wrapper that (de)serializes a [`QsoRecord`] as flat ADIF-named JSON
+#[derive(Debug, Clone)]
+pub struct AdifJson(pub QsoRecord);
+
+impl From<QsoRecord> for AdifJson {
+    fn from(qso: QsoRecord) -> Self {
+        AdifJson(qso)
+    }
+}
+
+impl From<AdifJson> for QsoRecord {
+    fn from(json: AdifJson) -> Self {
+        json.0
+    }
+}
+
+impl Serialize for AdifJson {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AdifJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(AdifJson)
+    }
+}
+
+/// Serialize a QSO record as flat ADIF-named JSON, for `#[serde(with)]`
+pub fn serialize<S: Serializer>(qso: &QsoRecord, serializer: S) -> Result<S::Ok, S::Error> {
+    let fields = AdifParser::to_fields(qso);
+    let mut map = serializer.serialize_map(Some(fields.len()))?;
+    for (name, value) in &fields {
+        map.serialize_entry(&name.to_uppercase(), value)?;
+    }
+    map.end()
+}
+
+/// Deserialize a QSO record from flat ADIF-named JSON, for `#[serde(with)]`
+///
+/// Field names are case-insensitive, and numbers are accepted as well as
+/// strings so that `{"FREQ": 14.074}` works.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<QsoRecord, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        String(String),
+        Unsigned(u64),
+        Signed(i64),
+        Float(f64),
+    }
+
+    let raw = HashMap::<String, Value>::deserialize(deserializer)?;
+    let fields = raw
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(s) => s,
+                Value::Unsigned(n) => n.to_string(),
+                Value::Signed(n) => n.to_string(),
+                Value::Float(n) => n.to_string(),
+            };
+            (name.to_lowercase(), value)
+        })
+        .collect();
+
+    AdifParser::fields_to_qso(fields).map_err(de::Error::custom)
+}
+
+/// Convert a QSO record to an ADIF-named JSON string
+pub fn to_string(qso: &QsoRecord) -> QrzLogbookResult<String> {
+    Ok(serde_json::to_string(&AdifJson(qso.clone()))?)
+}
+
+/// Parse a QSO record from an ADIF-named JSON string
+pub fn from_str(json: &str) -> QrzLogbookResult<QsoRecord> {
+    Ok(serde_json::from_str::<AdifJson>(json)?.0)
+}
+
+/// Write QSO records as newline-delimited JSON, one record per line
+pub fn write_ndjson<W: Write>(mut writer: W, qsos: &[QsoRecord]) -> QrzLogbookResult<()> {
+    for qso in qsos {
+        serde_json::to_writer(&mut writer, &AdifJson(qso.clone()))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Iterator over QSO records in newline-delimited JSON
+///
+/// Blank lines are skipped. Each item is a separate result, so a bad line
+/// does not stop the stream.
+pub struct NdjsonReader<R> {
+    lines: std::io::Lines<R>,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = QrzLogbookResult<QsoRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if !line.trim().is_empty() {
+                return Some(from_str(&line));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::QrzLogbookError, models::QslStatus};
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn sample_qso() -> QsoRecord {
+        QsoRecord::builder()
+            .call("JA1XY")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(12, 30, 15).unwrap())
+            .band("20m")
+            .mode("FT8")
+            .freq(14.074)
+            .dxcc(339)
+            .qsl_rcvd(QslStatus::Yes)
+            .additional_field("iota", "AS-007")
+            .build()
+    }
+
+    #[test]
+    fn test_serialize_flat_adif_names() {
+        let value = serde_json::to_value(AdifJson(sample_qso())).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "CALL": "JA1XY",
+                "STATION_CALLSIGN": "K1ABC",
+                "QSO_DATE": "20240315",
+                "TIME_ON": "123015",
+                "BAND": "20m",
+                "MODE": "FT8",
+                "FREQ": "14.074",
+                "DXCC": "339",
+                "QSL_RCVD": "Y",
+                "IOTA": "AS-007",
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_mixed_case_and_numbers() {
+        let qso = from_str(
+            r#"{"call":"W1AW","Station_Callsign":"K1ABC","QSO_DATE":"20240115","TIME_ON":"1430","BAND":"20m","MODE":"SSB","FREQ":14.2,"DXCC":291}"#,
+        )
+        .unwrap();
+        assert_eq!(qso.call, "W1AW");
+        assert_eq!(qso.freq, Some(14.2));
+        assert_eq!(qso.dxcc, Some(291));
+        assert!(qso.additional_fields.is_empty());
+
+        let result = from_str(r#"{"CALL":"W1AW"}"#);
+        assert!(matches!(result, Err(QrzLogbookError::Serialization(_))));
+    }
+
+    #[test]
+    fn test_serde_with_attribute() {
+        #[derive(Serialize, Deserialize)]
+        struct Upload {
+            logbook: String,
+            #[serde(with = "crate::json")]
+            qso: QsoRecord,
+        }
+
+        let upload = Upload {
+            logbook: "K1ABC".to_string(),
+            qso: sample_qso(),
+        };
+        let json = serde_json::to_string(&upload).unwrap();
+        assert!(json.contains(r#""qso":{"CALL":"JA1XY""#));
+
+        let parsed: Upload = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.qso.call, "JA1XY");
+        assert_eq!(parsed.qso.additional_fields["iota"], "AS-007");
+    }
+
+    #[test]
+    fn test_ndjson_roundtrip() {
+        let mut second = sample_qso();
+        second.call = "DL1AB".to_string();
+
+        let mut output = Vec::new();
+        write_ndjson(&mut output, &[sample_qso(), second]).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        let input = format!("{}\n\nnot json\n", text);
+        let records: Vec<_> = NdjsonReader::new(input.as_bytes()).collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap().call, "JA1XY");
+        assert_eq!(records[1].as_ref().unwrap().call, "DL1AB");
+        assert_eq!(
+            records[1].as_ref().unwrap().time_on,
+            NaiveTime::from_hms_opt(12, 30, 15).unwrap()
+        );
+        assert!(records[2].is_err());
+    }
+}
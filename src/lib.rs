@@ -9,6 +9,22 @@
 //! - Fetch QSO records with filtering
 //! - Get logbook status
 //! - Full ADIF support
+//! - Cabrillo 3.0 contest log export and import
+//! - CSV import and export with configurable column mapping
+//! - Flat ADIF-named JSON and NDJSON streaming
+//! - Client-side QSO query language with server-side pushdown
+//! - Cheap logid-only listings to detect server-side additions and deletions
+//! - Incremental downloads of records modified since the last sync
+//! - `tracing` spans and a metrics hook for every API request
+//! - Request/response middleware for headers, recording and fault injection
+//! - Pluggable transport with reqwest and in-memory implementations
+//! - Blocking client for synchronous programs (`blocking` feature)
+//! - Stateful fake QRZ Logbook server for end-to-end tests (`testing` feature)
+//! - Logbook backup to compressed archives and restore of missing QSOs
+//! - Multi-logbook profiles with callsign-based routing
+//! - WSJT-X UDP listener that uploads logged QSOs
+//! - N1MM Logger+ contact sync with edit and delete propagation
+//! - API keys redacted from logs and loadable from env, files or commands
 //! - Type-safe API with comprehensive error handling
 //!
 //! ## Example
@@ -19,7 +35,7 @@
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
-//!     let client = QrzLogbookClient::new("YOUR-API-KEY", "MyApp/1.0.0 (YOURCALL)")?;
+//!     let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
 //!     
 //!     let qso = QsoRecord::builder()
 //!         .call("W1AW")
@@ -38,10 +54,32 @@
 //! ```
 
 pub mod adif;
+pub mod backup;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cabrillo;
 pub mod client;
+pub mod csv;
 pub mod error;
+pub mod json;
+pub mod metrics;
+pub mod middleware;
 pub mod models;
+pub mod n1mm;
+pub mod profile;
+pub mod query;
+pub mod router;
+pub mod secret;
+pub mod sync;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+pub mod wsjtx;
 
 pub use client::QrzLogbookClient;
 pub use error::{QrzLogbookError, QrzLogbookResult};
 pub use models::*;
+pub use profile::{LogbookProfile, ProfileConfig};
+pub use router::LogbookRouter;
+pub use secret::ApiKey;
+pub use sync::LogidDiff;
@@ -0,0 +1,123 @@
+//! Request metrics hook
+//!
+//! [`QrzLogbookClient`](crate::QrzLogbookClient) reports every API call to a
+//! [`MetricsHook`] set with
+//! [`with_metrics`](crate::QrzLogbookClient::with_metrics). The hook receives
+//! plain counters and histograms so it can forward them to any metrics
+//! backend:
+//!
+//! - `qrz_requests_total` counter, labelled `action` and `result`
+//! - `qrz_request_duration_seconds` histogram, labelled `action` and `result`
+//!
+//! `result` is the API `RESULT` code (`OK`, `FAIL`, `AUTH`, ...) or `ERROR`
+//! when no response was received.
+//!
+//! # Example
+//! ```rust
+//! use qrz_logbook_api::metrics::{Labels, MetricsHook};
+//! use qrz_logbook_api::QrzLogbookClient;
+//!
+//! struct PrintMetrics;
+//!
+//! impl MetricsHook for PrintMetrics {
+//!     fn increment_counter(&self, name: &'static str, labels: Labels<'_>) {
+//!         println!("{} {:?} +1", name, labels);
+//!     }
+//!
+//!     fn record_histogram(&self, name: &'static str, value: f64, labels: Labels<'_>) {
+//!         println!("{} {:?} {}", name, labels, value);
+//!     }
+//! }
+//!
+//! let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (N0CALL)")
+//!     .unwrap()
+//!     .with_metrics(PrintMetrics);
+//! ```
+
+use std::sync::Mutex;
+
+/// Counter of API requests
+pub const REQUESTS_TOTAL: &str = "qrz_requests_total";
+/// Histogram of API request latency in seconds
+pub const REQUEST_DURATION_SECONDS: &str = "qrz_request_duration_seconds";
+
+/// Metric labels as `(name, value)` pairs
+pub type Labels<'a> = &'a [(&'static str, &'a str)];
+
+/// Receives request counters and histograms from the client
+pub trait MetricsHook: Send + Sync {
+    /// Increment a counter by one
+    fn increment_counter(&self, name: &'static str, labels: Labels<'_>);
+
+    /// Record a histogram observation
+    fn record_histogram(&self, name: &'static str, value: f64, labels: Labels<'_>);
+}
+
+/// A metric observation recorded by [`RecordingMetrics`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub name: &'static str,
+    /// `None` for counter increments
+    pub value: Option<f64>,
+    pub labels: Vec<(&'static str, String)>,
+}
+
+/// Metrics hook that keeps every observation in memory, for tests
+#[derive(Debug, Default)]
+pub struct RecordingMetrics {
+    observations: Mutex<Vec<Observation>>,
+}
+
+impl RecordingMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observations recorded so far
+    pub fn observations(&self) -> Vec<Observation> {
+        self.observations.lock().unwrap().clone()
+    }
+
+    /// Number of counter increments with the name and matching labels
+    pub fn counter(&self, name: &str, labels: Labels<'_>) -> u64 {
+        self.observations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|o| o.name == name && o.value.is_none())
+            .filter(|o| {
+                labels
+                    .iter()
+                    .all(|(k, v)| o.labels.iter().any(|(lk, lv)| lk == k && lv == v))
+            })
+            .count() as u64
+    }
+
+    fn push(&self, name: &'static str, value: Option<f64>, labels: Labels<'_>) {
+        self.observations.lock().unwrap().push(Observation {
+            name,
+            value,
+            labels: labels.iter().map(|(k, v)| (*k, v.to_string())).collect(),
+        });
+    }
+}
+
+impl MetricsHook for RecordingMetrics {
+    fn increment_counter(&self, name: &'static str, labels: Labels<'_>) {
+        self.push(name, None, labels);
+    }
+
+    fn record_histogram(&self, name: &'static str, value: f64, labels: Labels<'_>) {
+        self.push(name, Some(value), labels);
+    }
+}
+
+impl<T: MetricsHook + ?Sized> MetricsHook for std::sync::Arc<T> {
+    fn increment_counter(&self, name: &'static str, labels: Labels<'_>) {
+        (**self).increment_counter(name, labels);
+    }
+
+    fn record_histogram(&self, name: &'static str, value: f64, labels: Labels<'_>) {
+        (**self).record_histogram(name, value, labels);
+    }
+}
@@ -0,0 +1,124 @@
+//! Request/response middleware
+//!
+//! Middleware added with
+//! [`with_middleware`](crate::QrzLogbookClient::with_middleware) sees every
+//! API call. [`before_send`](Middleware::before_send) can change the form
+//! parameters or add HTTP headers, and
+//! [`after_receive`](Middleware::after_receive) can inspect or rewrite the
+//! raw response body. Either hook can fail the call by returning an error.
+//!
+//! `before_send` hooks run in the order the middleware was added and
+//! `after_receive` hooks in reverse order, so the first middleware wraps all
+//! the others. `after_receive` is not called when the transport fails.
+//!
+//! # Example
+//! ```rust
+//! use qrz_logbook_api::middleware::Middleware;
+//! use qrz_logbook_api::transport::TransportRequest;
+//! use qrz_logbook_api::{QrzLogbookClient, QrzLogbookResult};
+//!
+//! struct ProxyAuth(String);
+//!
+//! impl Middleware for ProxyAuth {
+//!     fn before_send(&self, request: &mut TransportRequest) -> QrzLogbookResult<()> {
+//!         request.add_header("Proxy-Authorization", self.0.clone());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (N0CALL)")
+//!     .unwrap()
+//!     .with_middleware(ProxyAuth("Basic dXNlcjpwYXNz".to_string()));
+//! ```
+
+use crate::{error::QrzLogbookResult, transport::TransportRequest};
+use std::sync::{Arc, Mutex};
+
+/// Hooks run around every API request
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent
+    fn before_send(&self, request: &mut TransportRequest) -> QrzLogbookResult<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called with the raw response body before it is parsed
+    fn after_receive(
+        &self,
+        request: &TransportRequest,
+        response: &mut String,
+    ) -> QrzLogbookResult<()> {
+        let _ = (request, response);
+        Ok(())
+    }
+}
+
+impl<T: Middleware + ?Sized> Middleware for Arc<T> {
+    fn before_send(&self, request: &mut TransportRequest) -> QrzLogbookResult<()> {
+        (**self).before_send(request)
+    }
+
+    fn after_receive(
+        &self,
+        request: &TransportRequest,
+        response: &mut String,
+    ) -> QrzLogbookResult<()> {
+        (**self).after_receive(request, response)
+    }
+}
+
+/// Middleware that adds an HTTP header to every request
+#[derive(Debug, Clone)]
+pub struct Header {
+    name: String,
+    value: String,
+}
+
+impl Header {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl Middleware for Header {
+    fn before_send(&self, request: &mut TransportRequest) -> QrzLogbookResult<()> {
+        request.add_header(self.name.clone(), self.value.clone());
+        Ok(())
+    }
+}
+
+/// Middleware that records every action and raw response body
+///
+/// Useful for debugging unexpected responses. Clones share the record.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseRecorder {
+    responses: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl ResponseRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `(action, body)` of every response received so far
+    pub fn responses(&self) -> Vec<(String, String)> {
+        self.responses.lock().unwrap().clone()
+    }
+}
+
+impl Middleware for ResponseRecorder {
+    fn after_receive(
+        &self,
+        request: &TransportRequest,
+        response: &mut String,
+    ) -> QrzLogbookResult<()> {
+        self.responses.lock().unwrap().push((
+            request.action().unwrap_or_default().to_string(),
+            response.clone(),
+        ));
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
-use chrono::{NaiveDate, NaiveTime};
+use crate::{
+    adif::AdifParser,
+    error::{QrzLogbookError, QrzLogbookResult},
+};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
 
 /// QSO record for the logbook
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +23,8 @@ pub struct QsoRecord {
     pub time_on: NaiveTime,
     /// Time off (end time, optional)
     pub time_off: Option<NaiveTime>,
+    /// Date of time off (optional, inferred from the times when missing)
+    pub qso_date_off: Option<NaiveDate>,
     /// Band (e.g., "20m", "40m")
     pub band: String,
     /// Mode (e.g., "SSB", "CW", "FT8")
@@ -31,6 +41,50 @@ pub struct QsoRecord {
     pub name: Option<String>,
     /// Comments (optional)
     pub comment: Option<String>,
+    /// Contacted station's Maidenhead locator (optional)
+    pub gridsquare: Option<String>,
+    /// Your Maidenhead locator (optional)
+    pub my_gridsquare: Option<String>,
+    /// Transmit power in watts (optional)
+    pub tx_pwr: Option<f64>,
+    /// Operator callsign when different from the station callsign (optional)
+    pub operator: Option<String>,
+    /// Contest identifier, e.g. "CQ-WW-CW" (optional)
+    pub contest_id: Option<String>,
+    /// Received contest serial number (optional)
+    pub srx: Option<u32>,
+    /// Sent contest serial number (optional)
+    pub stx: Option<u32>,
+    /// Received contest exchange (optional)
+    pub srx_string: Option<String>,
+    /// Sent contest exchange (optional)
+    pub stx_string: Option<String>,
+    /// Contacted station's state or province (optional)
+    pub state: Option<String>,
+    /// Contacted station's county (optional)
+    pub cnty: Option<String>,
+    /// Contacted station's DXCC entity code (optional)
+    pub dxcc: Option<u32>,
+    /// Contacted station's CQ zone (optional)
+    pub cqz: Option<u32>,
+    /// Contacted station's ITU zone (optional)
+    pub ituz: Option<u32>,
+    /// Propagation mode (optional)
+    pub prop_mode: Option<PropMode>,
+    /// Satellite name for satellite QSOs (optional)
+    pub sat_name: Option<String>,
+    /// Paper QSL sent status (optional)
+    pub qsl_sent: Option<QslStatus>,
+    /// Paper QSL received status (optional)
+    pub qsl_rcvd: Option<QslStatus>,
+    /// Paper QSL sent date (optional)
+    pub qslsdate: Option<NaiveDate>,
+    /// Paper QSL received date (optional)
+    pub qslrdate: Option<NaiveDate>,
+    /// LoTW QSL sent status (optional)
+    pub lotw_qsl_sent: Option<QslStatus>,
+    /// LoTW QSL received status (optional)
+    pub lotw_qsl_rcvd: Option<QslStatus>,
     /// Additional ADIF fields
     pub additional_fields: HashMap<String, String>,
 }
@@ -40,6 +94,295 @@ impl QsoRecord {
     pub fn builder() -> QsoRecordBuilder {
         QsoRecordBuilder::new()
     }
+
+    /// QSO start time in UTC
+    pub fn start_utc(&self) -> DateTime<Utc> {
+        self.qso_date.and_time(self.time_on).and_utc()
+    }
+
+    /// QSO end time in UTC, if `time_off` is set
+    ///
+    /// Without `qso_date_off`, a `time_off` earlier than `time_on` is taken
+    /// to be on the following day (e.g. 23:58 to 00:03).
+    pub fn end_utc(&self) -> Option<DateTime<Utc>> {
+        let time_off = self.time_off?;
+        let date_off = self.qso_date_off.unwrap_or_else(|| {
+            if time_off < self.time_on {
+                self.qso_date.succ_opt().unwrap_or(self.qso_date)
+            } else {
+                self.qso_date
+            }
+        });
+        Some(date_off.and_time(time_off).and_utc())
+    }
+
+    /// QSO duration, if `time_off` is set
+    pub fn duration(&self) -> Option<Duration> {
+        self.end_utc().map(|end| end - self.start_utc())
+    }
+
+    /// Fields QRZ uses to detect duplicate QSOs
+    pub(crate) fn match_key(&self) -> (String, NaiveDate, String, String, String) {
+        (
+            self.call.to_uppercase(),
+            self.qso_date,
+            self.time_on.format("%H%M").to_string(),
+            self.band.to_lowercase(),
+            self.mode.to_uppercase(),
+        )
+    }
+
+    /// Field-level differences from this record to `other`
+    ///
+    /// Built-in and additional fields are compared by their ADIF values, with
+    /// field names matched case-insensitively. Frequencies are compared as
+    /// numbers, so `14.074` and `14.0740` are equal.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use qrz_logbook_api::QsoRecord;
+    /// let local = QsoRecord::builder().call("W1AW").name("Hiram").build();
+    /// let server = QsoRecord::builder().call("W1AW").build();
+    ///
+    /// let diff = server.diff(&local);
+    /// assert_eq!(diff.len(), 1);
+    /// assert_eq!(diff.get("name").unwrap().new.as_deref(), Some("Hiram"));
+    /// ```
+    pub fn diff(&self, other: &QsoRecord) -> QsoDiff {
+        let old: BTreeMap<String, String> = AdifParser::to_fields(self).into_iter().collect();
+        let mut new: BTreeMap<String, String> = AdifParser::to_fields(other).into_iter().collect();
+
+        let mut changes = BTreeMap::new();
+        for (field, old_value) in old {
+            let new_value = new.remove(&field);
+            if !field_values_equal(&field, Some(&old_value), new_value.as_ref()) {
+                changes.insert(
+                    field,
+                    FieldChange {
+                        old: Some(old_value),
+                        new: new_value,
+                    },
+                );
+            }
+        }
+        for (field, new_value) in new {
+            changes.insert(
+                field,
+                FieldChange {
+                    old: None,
+                    new: Some(new_value),
+                },
+            );
+        }
+
+        QsoDiff { changes }
+    }
+}
+
+fn field_values_equal(field: &str, old: Option<&String>, new: Option<&String>) -> bool {
+    match (old, new) {
+        (Some(old), Some(new)) if matches!(field, "freq" | "freq_rx") => {
+            match (old.trim().parse::<f64>(), new.trim().parse::<f64>()) {
+                (Ok(old), Ok(new)) => (old - new).abs() < 1e-9,
+                _ => old == new,
+            }
+        }
+        _ => old == new,
+    }
+}
+
+/// ADIF QSL status (`QSL_SENT`, `QSL_RCVD`, `LOTW_QSL_SENT`, ...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum QslStatus {
+    /// Y: sent or received
+    Yes,
+    /// N: not sent or not received
+    No,
+    /// R: requested
+    Requested,
+    /// Q: queued to be sent
+    Queued,
+    /// I: ignore or invalid
+    Ignore,
+    /// V: verified (received only)
+    Verified,
+}
+
+impl QslStatus {
+    /// ADIF enumeration value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QslStatus::Yes => "Y",
+            QslStatus::No => "N",
+            QslStatus::Requested => "R",
+            QslStatus::Queued => "Q",
+            QslStatus::Ignore => "I",
+            QslStatus::Verified => "V",
+        }
+    }
+}
+
+impl FromStr for QslStatus {
+    type Err = QrzLogbookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "Y" => Ok(QslStatus::Yes),
+            "N" => Ok(QslStatus::No),
+            "R" => Ok(QslStatus::Requested),
+            "Q" => Ok(QslStatus::Queued),
+            "I" => Ok(QslStatus::Ignore),
+            "V" => Ok(QslStatus::Verified),
+            _ => Err(QrzLogbookError::adif_parse(format!(
+                "Invalid QSL status: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for QslStatus {
+    type Error = QrzLogbookError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<QslStatus> for String {
+    fn from(status: QslStatus) -> Self {
+        status.as_str().to_string()
+    }
+}
+
+impl fmt::Display for QslStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// ADIF propagation mode (`PROP_MODE`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PropMode {
+    /// AS: aircraft scatter
+    AircraftScatter,
+    /// AUE: aurora-E
+    AuroraE,
+    /// AUR: aurora
+    Aurora,
+    /// BS: back scatter
+    BackScatter,
+    /// ECH: EchoLink
+    EchoLink,
+    /// EME: earth-moon-earth
+    Eme,
+    /// ES: sporadic E
+    SporadicE,
+    /// F2: F2 reflection
+    F2,
+    /// FAI: field aligned irregularities
+    Fai,
+    /// GWAVE: ground wave
+    GroundWave,
+    /// INTERNET: internet-assisted
+    Internet,
+    /// ION: ionoscatter
+    Ionoscatter,
+    /// IRL: IRLP
+    Irlp,
+    /// LOS: line of sight
+    LineOfSight,
+    /// MS: meteor scatter
+    MeteorScatter,
+    /// RPT: terrestrial or atmospheric repeater or transponder
+    Repeater,
+    /// RS: rain scatter
+    RainScatter,
+    /// SAT: satellite
+    Satellite,
+    /// TEP: trans-equatorial
+    TransEquatorial,
+    /// TR: tropospheric ducting
+    Tropo,
+    /// Any value not defined by the ADIF specification
+    Other(String),
+}
+
+impl PropMode {
+    /// ADIF enumeration value
+    pub fn as_str(&self) -> &str {
+        match self {
+            PropMode::AircraftScatter => "AS",
+            PropMode::AuroraE => "AUE",
+            PropMode::Aurora => "AUR",
+            PropMode::BackScatter => "BS",
+            PropMode::EchoLink => "ECH",
+            PropMode::Eme => "EME",
+            PropMode::SporadicE => "ES",
+            PropMode::F2 => "F2",
+            PropMode::Fai => "FAI",
+            PropMode::GroundWave => "GWAVE",
+            PropMode::Internet => "INTERNET",
+            PropMode::Ionoscatter => "ION",
+            PropMode::Irlp => "IRL",
+            PropMode::LineOfSight => "LOS",
+            PropMode::MeteorScatter => "MS",
+            PropMode::Repeater => "RPT",
+            PropMode::RainScatter => "RS",
+            PropMode::Satellite => "SAT",
+            PropMode::TransEquatorial => "TEP",
+            PropMode::Tropo => "TR",
+            PropMode::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for PropMode {
+    fn from(s: &str) -> Self {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "AS" => PropMode::AircraftScatter,
+            "AUE" => PropMode::AuroraE,
+            "AUR" => PropMode::Aurora,
+            "BS" => PropMode::BackScatter,
+            "ECH" => PropMode::EchoLink,
+            "EME" => PropMode::Eme,
+            "ES" => PropMode::SporadicE,
+            "F2" => PropMode::F2,
+            "FAI" => PropMode::Fai,
+            "GWAVE" => PropMode::GroundWave,
+            "INTERNET" => PropMode::Internet,
+            "ION" => PropMode::Ionoscatter,
+            "IRL" => PropMode::Irlp,
+            "LOS" => PropMode::LineOfSight,
+            "MS" => PropMode::MeteorScatter,
+            "RPT" => PropMode::Repeater,
+            "RS" => PropMode::RainScatter,
+            "SAT" => PropMode::Satellite,
+            "TEP" => PropMode::TransEquatorial,
+            "TR" => PropMode::Tropo,
+            _ => PropMode::Other(s.trim().to_string()),
+        }
+    }
+}
+
+impl From<String> for PropMode {
+    fn from(s: String) -> Self {
+        PropMode::from(s.as_str())
+    }
+}
+
+impl From<PropMode> for String {
+    fn from(mode: PropMode) -> Self {
+        mode.as_str().to_string()
+    }
+}
+
+impl fmt::Display for PropMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Builder for QSO records
@@ -50,6 +393,7 @@ pub struct QsoRecordBuilder {
     qso_date: Option<NaiveDate>,
     time_on: Option<NaiveTime>,
     time_off: Option<NaiveTime>,
+    qso_date_off: Option<NaiveDate>,
     band: Option<String>,
     mode: Option<String>,
     freq: Option<f64>,
@@ -58,6 +402,28 @@ pub struct QsoRecordBuilder {
     qth: Option<String>,
     name: Option<String>,
     comment: Option<String>,
+    gridsquare: Option<String>,
+    my_gridsquare: Option<String>,
+    tx_pwr: Option<f64>,
+    operator: Option<String>,
+    contest_id: Option<String>,
+    srx: Option<u32>,
+    stx: Option<u32>,
+    srx_string: Option<String>,
+    stx_string: Option<String>,
+    state: Option<String>,
+    cnty: Option<String>,
+    dxcc: Option<u32>,
+    cqz: Option<u32>,
+    ituz: Option<u32>,
+    prop_mode: Option<PropMode>,
+    sat_name: Option<String>,
+    qsl_sent: Option<QslStatus>,
+    qsl_rcvd: Option<QslStatus>,
+    qslsdate: Option<NaiveDate>,
+    qslrdate: Option<NaiveDate>,
+    lotw_qsl_sent: Option<QslStatus>,
+    lotw_qsl_rcvd: Option<QslStatus>,
     additional_fields: HashMap<String, String>,
 }
 
@@ -91,6 +457,31 @@ impl QsoRecordBuilder {
         self
     }
 
+    /// Date of time off, for QSOs that end on a later day
+    pub fn date_off(mut self, date: NaiveDate) -> Self {
+        self.qso_date_off = Some(date);
+        self
+    }
+
+    /// Set `qso_date` and `time_on` from a timestamp in any timezone
+    ///
+    /// The timestamp is converted to UTC, e.g. `start(Utc::now())` or
+    /// `start(Local::now())`.
+    pub fn start<Tz: TimeZone>(mut self, start: DateTime<Tz>) -> Self {
+        let start = start.naive_utc();
+        self.qso_date = Some(start.date());
+        self.time_on = Some(start.time());
+        self
+    }
+
+    /// Set `qso_date_off` and `time_off` from a timestamp in any timezone
+    pub fn end<Tz: TimeZone>(mut self, end: DateTime<Tz>) -> Self {
+        let end = end.naive_utc();
+        self.qso_date_off = Some(end.date());
+        self.time_off = Some(end.time());
+        self
+    }
+
     pub fn band(mut self, band: impl Into<String>) -> Self {
         self.band = Some(band.into());
         self
@@ -131,6 +522,116 @@ impl QsoRecordBuilder {
         self
     }
 
+    pub fn gridsquare(mut self, grid: impl Into<String>) -> Self {
+        self.gridsquare = Some(grid.into());
+        self
+    }
+
+    pub fn my_gridsquare(mut self, grid: impl Into<String>) -> Self {
+        self.my_gridsquare = Some(grid.into());
+        self
+    }
+
+    pub fn tx_pwr(mut self, watts: f64) -> Self {
+        self.tx_pwr = Some(watts);
+        self
+    }
+
+    pub fn operator(mut self, callsign: impl Into<String>) -> Self {
+        self.operator = Some(callsign.into());
+        self
+    }
+
+    pub fn contest_id(mut self, contest: impl Into<String>) -> Self {
+        self.contest_id = Some(contest.into());
+        self
+    }
+
+    pub fn srx(mut self, serial: u32) -> Self {
+        self.srx = Some(serial);
+        self
+    }
+
+    pub fn stx(mut self, serial: u32) -> Self {
+        self.stx = Some(serial);
+        self
+    }
+
+    pub fn srx_string(mut self, exchange: impl Into<String>) -> Self {
+        self.srx_string = Some(exchange.into());
+        self
+    }
+
+    pub fn stx_string(mut self, exchange: impl Into<String>) -> Self {
+        self.stx_string = Some(exchange.into());
+        self
+    }
+
+    pub fn state(mut self, state: impl Into<String>) -> Self {
+        self.state = Some(state.into());
+        self
+    }
+
+    pub fn cnty(mut self, county: impl Into<String>) -> Self {
+        self.cnty = Some(county.into());
+        self
+    }
+
+    pub fn dxcc(mut self, dxcc: u32) -> Self {
+        self.dxcc = Some(dxcc);
+        self
+    }
+
+    pub fn cqz(mut self, zone: u32) -> Self {
+        self.cqz = Some(zone);
+        self
+    }
+
+    pub fn ituz(mut self, zone: u32) -> Self {
+        self.ituz = Some(zone);
+        self
+    }
+
+    pub fn prop_mode(mut self, mode: impl Into<PropMode>) -> Self {
+        self.prop_mode = Some(mode.into());
+        self
+    }
+
+    pub fn sat_name(mut self, name: impl Into<String>) -> Self {
+        self.sat_name = Some(name.into());
+        self
+    }
+
+    pub fn qsl_sent(mut self, status: QslStatus) -> Self {
+        self.qsl_sent = Some(status);
+        self
+    }
+
+    pub fn qsl_rcvd(mut self, status: QslStatus) -> Self {
+        self.qsl_rcvd = Some(status);
+        self
+    }
+
+    pub fn qslsdate(mut self, date: NaiveDate) -> Self {
+        self.qslsdate = Some(date);
+        self
+    }
+
+    pub fn qslrdate(mut self, date: NaiveDate) -> Self {
+        self.qslrdate = Some(date);
+        self
+    }
+
+    pub fn lotw_qsl_sent(mut self, status: QslStatus) -> Self {
+        self.lotw_qsl_sent = Some(status);
+        self
+    }
+
+    pub fn lotw_qsl_rcvd(mut self, status: QslStatus) -> Self {
+        self.lotw_qsl_rcvd = Some(status);
+        self
+    }
+
     pub fn additional_field(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.additional_fields.insert(key.into(), value.into());
         self
@@ -147,6 +648,7 @@ impl QsoRecordBuilder {
                 .time_on
                 .unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
             time_off: self.time_off,
+            qso_date_off: self.qso_date_off,
             band: self.band.unwrap_or_default(),
             mode: self.mode.unwrap_or_default(),
             freq: self.freq,
@@ -155,6 +657,28 @@ impl QsoRecordBuilder {
             qth: self.qth,
             name: self.name,
             comment: self.comment,
+            gridsquare: self.gridsquare,
+            my_gridsquare: self.my_gridsquare,
+            tx_pwr: self.tx_pwr,
+            operator: self.operator,
+            contest_id: self.contest_id,
+            srx: self.srx,
+            stx: self.stx,
+            srx_string: self.srx_string,
+            stx_string: self.stx_string,
+            state: self.state,
+            cnty: self.cnty,
+            dxcc: self.dxcc,
+            cqz: self.cqz,
+            ituz: self.ituz,
+            prop_mode: self.prop_mode,
+            sat_name: self.sat_name,
+            qsl_sent: self.qsl_sent,
+            qsl_rcvd: self.qsl_rcvd,
+            qslsdate: self.qslsdate,
+            qslrdate: self.qslrdate,
+            lotw_qsl_sent: self.lotw_qsl_sent,
+            lotw_qsl_rcvd: self.lotw_qsl_rcvd,
             additional_fields: self.additional_fields,
         }
     }
@@ -165,6 +689,195 @@ impl QsoRecordBuilder {
 pub struct InsertResponse {
     pub logid: u64,
     pub count: u32,
+    /// Whether the REPLACE option overwrote an existing QSO
+    pub replaced: bool,
+}
+
+/// Field-level changes to a QSO record, keyed by ADIF field name
+///
+/// Used with [`update_qso`](crate::QrzLogbookClient::update_qso). Values use
+/// ADIF formats, as in [`AdifParser::to_fields`](crate::adif::AdifParser::to_fields).
+/// Serializes as a JSON object of field names to values, with `null` for
+/// removed fields.
+///
+/// # Example
+/// ```rust
+/// use qrz_logbook_api::QsoPatch;
+///
+/// let patch = QsoPatch::new()
+///     .set("name", "Hiram")
+///     .set("rst_rcvd", "579")
+///     .clear("comment");
+/// assert_eq!(patch.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<String, Option<String>>",
+    into = "BTreeMap<String, Option<String>>"
+)]
+pub struct QsoPatch {
+    /// New value per field; `None` removes the field
+    pub changes: BTreeMap<String, Option<String>>,
+}
+
+impl QsoPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a field to a value in ADIF format
+    pub fn set(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.changes
+            .insert(field.into().to_lowercase(), Some(value.into()));
+        self
+    }
+
+    /// Remove an optional field
+    pub fn clear(mut self, field: impl Into<String>) -> Self {
+        self.changes.insert(field.into().to_lowercase(), None);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Apply the changes to a QSO record
+    ///
+    /// Fails if a value is invalid for its field or a required field is
+    /// cleared.
+    pub fn apply(&self, qso: &QsoRecord) -> QrzLogbookResult<QsoRecord> {
+        let mut fields: HashMap<String, String> = AdifParser::to_fields(qso).into_iter().collect();
+        for (field, value) in &self.changes {
+            let field = field.to_lowercase();
+            match value {
+                Some(value) => fields.insert(field, value.clone()),
+                None => fields.remove(&field),
+            };
+        }
+        AdifParser::fields_to_qso(fields)
+    }
+}
+
+impl From<BTreeMap<String, Option<String>>> for QsoPatch {
+    fn from(changes: BTreeMap<String, Option<String>>) -> Self {
+        Self {
+            changes: changes
+                .into_iter()
+                .map(|(field, value)| (field.to_lowercase(), value))
+                .collect(),
+        }
+    }
+}
+
+impl From<QsoPatch> for BTreeMap<String, Option<String>> {
+    fn from(patch: QsoPatch) -> Self {
+        patch.changes
+    }
+}
+
+/// Old and new value of a changed field; `None` means the field is not set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Field-level differences between two QSO records, from [`QsoRecord::diff`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<String, FieldChange>",
+    into = "BTreeMap<String, FieldChange>"
+)]
+pub struct QsoDiff {
+    /// Changed fields by lowercase ADIF field name
+    pub changes: BTreeMap<String, FieldChange>,
+}
+
+impl QsoDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// The change to a field, if it differs
+    pub fn get(&self, field: &str) -> Option<&FieldChange> {
+        self.changes.get(&field.to_lowercase())
+    }
+
+    /// Names of the changed fields
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.changes.keys().map(|s| s.as_str())
+    }
+
+    /// Patch that turns the old record into the new one
+    pub fn to_patch(&self) -> QsoPatch {
+        QsoPatch {
+            changes: self
+                .changes
+                .iter()
+                .map(|(field, change)| (field.clone(), change.new.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl From<BTreeMap<String, FieldChange>> for QsoDiff {
+    fn from(changes: BTreeMap<String, FieldChange>) -> Self {
+        Self {
+            changes: changes
+                .into_iter()
+                .map(|(field, change)| (field.to_lowercase(), change))
+                .collect(),
+        }
+    }
+}
+
+impl From<QsoDiff> for BTreeMap<String, FieldChange> {
+    fn from(diff: QsoDiff) -> Self {
+        diff.changes
+    }
+}
+
+impl fmt::Display for QsoDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (field, change) in &self.changes {
+            writeln!(
+                f,
+                "{}: {} -> {}",
+                field,
+                change.old.as_deref().unwrap_or("(unset)"),
+                change.new.as_deref().unwrap_or("(unset)")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`update_qso`](crate::QrzLogbookClient::update_qso)
+#[derive(Debug)]
+pub struct UpdateResponse {
+    /// Logid of the record holding the updated QSO
+    pub logid: u64,
+    /// Logid of the record that was updated
+    pub previous_logid: u64,
+    /// `true` if QRZ replaced the record in place; `false` if a new record
+    /// was created, e.g. because the patch changed the callsign, date, time,
+    /// band or mode
+    pub replaced: bool,
+    /// The QSO as uploaded
+    pub qso: QsoRecord,
+    /// Why the previous record could not be deleted after a new one was
+    /// created; if this is set the logbook holds both `previous_logid` and
+    /// `logid`, and the caller has to remove the duplicate
+    pub delete_error: Option<QrzLogbookError>,
 }
 
 /// Response from DELETE action
@@ -174,12 +887,92 @@ pub struct DeleteResponse {
     pub not_found_logids: Vec<u64>,
 }
 
+/// Options for [`delete_matching`](crate::QrzLogbookClient::delete_matching)
+#[derive(Debug, Clone)]
+pub struct DeleteOptions {
+    /// Only resolve the matching logids, don't delete anything
+    pub dry_run: bool,
+    /// Maximum number of logids per DELETE request
+    pub chunk_size: usize,
+    /// Deleting more QSOs than this requires a confirmation token
+    pub confirm_above: usize,
+    /// Token from a dry run confirming the exact set of logids to delete
+    pub confirmation_token: Option<String>,
+}
+
+impl Default for DeleteOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            chunk_size: 100,
+            confirm_above: 25,
+            confirmation_token: None,
+        }
+    }
+}
+
+impl DeleteOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Preview the deletion without deleting anything
+    pub fn dry_run() -> Self {
+        Self {
+            dry_run: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn confirm_above(mut self, threshold: usize) -> Self {
+        self.confirm_above = threshold;
+        self
+    }
+
+    pub fn confirm(mut self, token: impl Into<String>) -> Self {
+        self.confirmation_token = Some(token.into());
+        self
+    }
+}
+
+/// Result of [`delete_matching`](crate::QrzLogbookClient::delete_matching)
+#[derive(Debug, Clone)]
+pub struct DeleteReport {
+    /// Logids that matched the filter, in ascending order
+    pub logids: Vec<u64>,
+    /// Token to pass to [`DeleteOptions::confirm`] to delete these logids
+    pub confirmation_token: String,
+    /// Whether this was a dry run that deleted nothing
+    pub dry_run: bool,
+    /// Number of QSOs deleted
+    pub deleted_count: u32,
+    /// Logids QRZ no longer had when deleting
+    pub not_found_logids: Vec<u64>,
+    /// Chunks whose DELETE request failed, with the reason; the other chunks
+    /// were still attempted
+    pub failed_chunks: Vec<(Vec<u64>, String)>,
+}
+
 /// Response from STATUS action
 #[derive(Debug, Clone)]
 pub struct StatusResponse {
     pub data: HashMap<String, String>,
 }
 
+/// Logbook an API key belongs to, from [`verify_key`](crate::QrzLogbookClient::verify_key)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    /// Callsign of the logbook owner
+    pub callsign: String,
+    /// QRZ logbook ID
+    pub book_id: Option<String>,
+}
+
 /// Response from FETCH action
 #[derive(Debug, Clone)]
 pub struct FetchResponse {
@@ -188,6 +981,56 @@ pub struct FetchResponse {
     pub qsos: Vec<QsoRecord>,
 }
 
+/// Confirmation status filter for FETCH (`STATUS`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchStatus {
+    Confirmed,
+    Unconfirmed,
+}
+
+impl FetchStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FetchStatus::Confirmed => "CONFIRMED",
+            FetchStatus::Unconfirmed => "UNCONFIRMED",
+        }
+    }
+}
+
+/// Response content for FETCH (`TYPE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchType {
+    /// Full ADIF records (the server default)
+    Adif,
+    /// Only the logids of matching records
+    Logids,
+}
+
+impl FetchType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FetchType::Adif => "ADIF",
+            FetchType::Logids => "LOGIDS",
+        }
+    }
+}
+
+/// What [`insert_qso`](crate::QrzLogbookClient::insert_qso) does with a QSO
+/// whose station callsign doesn't match the logbook owner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallsignPolicy {
+    /// Upload without checking and let QRZ decide
+    #[default]
+    Allow,
+    /// Fail with [`QrzLogbookError::CallsignMismatch`](crate::QrzLogbookError::CallsignMismatch)
+    /// before uploading
+    Reject,
+    /// Upload portable variants of the owner callsign (`VE3/K1ABC/P`) under
+    /// the owner callsign, keeping the portable callsign in `operator`.
+    /// Other mismatches are rejected.
+    Rewrite,
+}
+
 /// Fetch options for filtering QSOs
 #[derive(Debug, Clone, Default)]
 pub struct FetchOptions {
@@ -207,6 +1050,16 @@ pub struct FetchOptions {
     pub date_from: Option<NaiveDate>,
     /// Filter by date range (end)
     pub date_to: Option<NaiveDate>,
+    /// Filter by confirmation status
+    pub status: Option<FetchStatus>,
+    /// Only records modified on or after this date
+    pub modsince: Option<NaiveDate>,
+    /// Fetch these specific records; cannot be combined with other filters
+    pub logids: Vec<u64>,
+    /// Response content (ADIF records or logids only)
+    pub fetch_type: Option<FetchType>,
+    /// Filter by DXCC entity code
+    pub dxcc: Option<u32>,
 }
 
 impl FetchOptions {
@@ -252,6 +1105,74 @@ impl FetchOptions {
         self
     }
 
+    pub fn status(mut self, status: FetchStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Only records modified on or after `date`
+    pub fn modified_since(mut self, date: NaiveDate) -> Self {
+        self.modsince = Some(date);
+        self
+    }
+
+    /// Fetch specific records by logid
+    pub fn logids(mut self, logids: impl IntoIterator<Item = u64>) -> Self {
+        self.logids = logids.into_iter().collect();
+        self
+    }
+
+    pub fn fetch_type(mut self, fetch_type: FetchType) -> Self {
+        self.fetch_type = Some(fetch_type);
+        self
+    }
+
+    pub fn dxcc(mut self, dxcc: u32) -> Self {
+        self.dxcc = Some(dxcc);
+        self
+    }
+
+    /// Check for option combinations the server does not accept
+    ///
+    /// `LOGIDS` selects records directly, so it cannot be combined with
+    /// filters, paging or `TYPE:LOGIDS`. A date range must not end before it
+    /// starts.
+    pub fn validate(&self) -> QrzLogbookResult<()> {
+        if !self.logids.is_empty() {
+            let filtered = self.all
+                || self.band.is_some()
+                || self.mode.is_some()
+                || self.call.is_some()
+                || self.after_logid.is_some()
+                || self.date_from.is_some()
+                || self.date_to.is_some()
+                || self.status.is_some()
+                || self.modsince.is_some()
+                || self.dxcc.is_some();
+            if filtered {
+                return Err(QrzLogbookError::invalid_params(
+                    "LOGIDS cannot be combined with other fetch filters",
+                ));
+            }
+
+            if self.fetch_type == Some(FetchType::Logids) {
+                return Err(QrzLogbookError::invalid_params(
+                    "LOGIDS cannot be combined with TYPE:LOGIDS",
+                ));
+            }
+        }
+
+        if let (Some(from), Some(to)) = (self.date_from, self.date_to) {
+            if from > to {
+                return Err(QrzLogbookError::invalid_params(
+                    "DATEFROM must not be after DATETO",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Convert to API option string
     pub fn to_option_string(&self) -> String {
         let mut options = Vec::new();
@@ -288,6 +1209,33 @@ impl FetchOptions {
             options.push(format!("DATETO:{}", date.format("%Y%m%d")));
         }
 
+        if let Some(status) = self.status {
+            options.push(format!("STATUS:{}", status.as_str()));
+        }
+
+        if let Some(date) = self.modsince {
+            options.push(format!("MODSINCE:{}", date.format("%Y-%m-%d")));
+        }
+
+        if let Some(dxcc) = self.dxcc {
+            options.push(format!("DXCC:{}", dxcc));
+        }
+
+        if let Some(fetch_type) = self.fetch_type {
+            options.push(format!("TYPE:{}", fetch_type.as_str()));
+        }
+
+        // The logid list is itself comma separated, so it goes last
+        if !self.logids.is_empty() {
+            let logids = self
+                .logids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            options.push(format!("LOGIDS:{}", logids));
+        }
+
         options.join(",")
     }
 }
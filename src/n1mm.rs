@@ -0,0 +1,723 @@
+//! N1MM Logger+ UDP contact broadcast support
+//!
+//! N1MM Logger+ broadcasts XML `contactinfo`, `contactreplace` and
+//! `contactdelete` packets whenever a contact is logged, edited or deleted.
+//! [`N1mmListener`] turns these into logbook operations and keeps a
+//! [`ContactMap`] of N1MM contact IDs to QRZ logids so edits and deletions in
+//! the contest log reach the QRZ logbook.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::{n1mm::{ContactMap, N1mmListener}, QrzLogbookClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
+//! let contacts = ContactMap::load("n1mm-contacts.json")?;
+//! let mut listener = N1mmListener::bind("127.0.0.1:12060", contacts).await?;
+//!
+//! listener
+//!     .run(&client, |result| match result {
+//!         Ok(action) => println!("{:?}", action),
+//!         Err(e) => eprintln!("N1MM sync failed: {}", e),
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::{band_for_freq, AdifParser},
+    client::QrzLogbookClient,
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::QsoRecord,
+};
+use chrono::NaiveDateTime;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+/// Default UDP port N1MM Logger+ broadcasts contacts to
+pub const DEFAULT_PORT: u16 = 12060;
+
+/// Contact event broadcast by N1MM Logger+
+#[derive(Debug, Clone, PartialEq)]
+pub enum N1mmEvent {
+    /// A new contact was logged
+    ContactInfo(N1mmContact),
+    /// An existing contact was edited
+    ContactReplace(N1mmContact),
+    /// A contact was deleted
+    ContactDelete(N1mmContactDelete),
+    /// Any other N1MM packet (radio info, spots, ...), by root element name
+    Other(String),
+}
+
+impl N1mmEvent {
+    /// Parse an N1MM XML packet
+    pub fn parse(xml: &str) -> QrzLogbookResult<Self> {
+        let document = roxmltree::Document::parse(xml.trim_start_matches('\u{feff}'))
+            .map_err(|e| QrzLogbookError::protocol(format!("Invalid N1MM XML: {}", e)))?;
+        let root = document.root_element();
+
+        let mut fields = HashMap::new();
+        for child in root.children().filter(|n| n.is_element()) {
+            fields.insert(
+                child.tag_name().name().to_lowercase(),
+                child.text().unwrap_or_default().trim().to_string(),
+            );
+        }
+
+        match root.tag_name().name().to_lowercase().as_str() {
+            "contactinfo" => Ok(Self::ContactInfo(N1mmContact::from_fields(fields)?)),
+            "contactreplace" => Ok(Self::ContactReplace(N1mmContact::from_fields(fields)?)),
+            "contactdelete" => Ok(Self::ContactDelete(N1mmContactDelete {
+                id: required(&fields, "id")?,
+                call: fields.get("call").cloned().unwrap_or_default(),
+                timestamp: fields
+                    .get("timestamp")
+                    .map(|s| parse_timestamp(s))
+                    .transpose()?,
+            })),
+            other => Ok(Self::Other(other.to_string())),
+        }
+    }
+}
+
+/// Contact from a `contactinfo` or `contactreplace` packet
+#[derive(Debug, Clone, PartialEq)]
+pub struct N1mmContact {
+    /// N1MM contact ID, stable across edits
+    pub id: String,
+    /// Start of the QSO in UTC
+    pub timestamp: NaiveDateTime,
+    pub call: String,
+    pub mycall: String,
+    /// Band in MHz as sent by N1MM (e.g. "3.5", "14")
+    pub band: String,
+    /// Transmit frequency in Hz
+    pub tx_freq: Option<u64>,
+    pub mode: String,
+    /// True unless the contact was relayed from another networked computer
+    pub is_original: bool,
+    /// All packet fields, keyed by lowercase element name
+    pub fields: HashMap<String, String>,
+}
+
+impl N1mmContact {
+    fn from_fields(fields: HashMap<String, String>) -> QrzLogbookResult<Self> {
+        // N1MM frequencies are in units of 10 Hz
+        let tx_freq = fields
+            .get("txfreq")
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse::<u64>()
+                    .map_err(|_| QrzLogbookError::protocol(format!("Invalid txfreq: {}", s)))
+            })
+            .transpose()?
+            .filter(|freq| *freq > 0)
+            .map(|freq| freq * 10);
+
+        Ok(Self {
+            id: required(&fields, "id")?,
+            timestamp: parse_timestamp(&required(&fields, "timestamp")?)?,
+            call: required(&fields, "call")?,
+            mycall: required(&fields, "mycall")?,
+            band: fields.get("band").cloned().unwrap_or_default(),
+            tx_freq,
+            mode: required(&fields, "mode")?,
+            is_original: !fields
+                .get("isoriginal")
+                .is_some_and(|s| s.eq_ignore_ascii_case("false")),
+            fields,
+        })
+    }
+
+    /// Convert the contact into a QSO record
+    pub fn to_qso_record(&self) -> QrzLogbookResult<QsoRecord> {
+        let mut adif = HashMap::new();
+        adif.insert("call".to_string(), self.call.to_uppercase());
+        adif.insert("station_callsign".to_string(), self.mycall.to_uppercase());
+        adif.insert(
+            "qso_date".to_string(),
+            self.timestamp.format("%Y%m%d").to_string(),
+        );
+        adif.insert(
+            "time_on".to_string(),
+            self.timestamp.format("%H%M%S").to_string(),
+        );
+
+        let freq_mhz = self.tx_freq.map(|hz| hz as f64 / 1_000_000.0);
+        let band = freq_mhz
+            .or_else(|| self.band.parse().ok())
+            .and_then(band_for_freq)
+            .ok_or_else(|| {
+                QrzLogbookError::protocol(format!("Unknown band for contact {}", self.id))
+            })?;
+        adif.insert("band".to_string(), band.to_string());
+        if let Some(freq) = freq_mhz {
+            adif.insert("freq".to_string(), format!("{:.5}", freq));
+        }
+
+        match self.mode.to_uppercase().as_str() {
+            sideband @ ("USB" | "LSB") => {
+                adif.insert("mode".to_string(), "SSB".to_string());
+                adif.insert("submode".to_string(), sideband.to_string());
+            }
+            mode => {
+                adif.insert("mode".to_string(), mode.to_string());
+            }
+        }
+
+        let mapped = [
+            ("snt", "rst_sent"),
+            ("rcv", "rst_rcvd"),
+            ("sntnr", "stx"),
+            ("rcvnr", "srx"),
+            ("exchange1", "srx_string"),
+            ("section", "arrl_sect"),
+            ("gridsquare", "gridsquare"),
+            ("name", "name"),
+            ("qth", "qth"),
+            ("comment", "comment"),
+            ("power", "rx_pwr"),
+            ("operator", "operator"),
+            ("contestname", "contest_id"),
+        ];
+        for (n1mm, field) in mapped {
+            let value = self.fields.get(n1mm).map(|s| s.trim()).unwrap_or_default();
+            // Serial numbers are 0 when the contest doesn't use them
+            if !value.is_empty() && !((n1mm == "sntnr" || n1mm == "rcvnr") && value == "0") {
+                adif.insert(field.to_string(), value.to_string());
+            }
+        }
+
+        AdifParser::fields_to_qso(adif)
+    }
+}
+
+/// Contact from a `contactdelete` packet
+#[derive(Debug, Clone, PartialEq)]
+pub struct N1mmContactDelete {
+    pub id: String,
+    pub call: String,
+    pub timestamp: Option<NaiveDateTime>,
+}
+
+/// Persistent mapping of N1MM contact IDs to QRZ logids
+#[derive(Debug, Clone, Default)]
+pub struct ContactMap {
+    path: Option<PathBuf>,
+    logids: HashMap<String, u64>,
+}
+
+impl ContactMap {
+    /// Create an in-memory map that is never persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the map from a JSON file, starting empty if the file doesn't exist
+    ///
+    /// The map is written back to the same file after every change.
+    pub fn load(path: impl AsRef<Path>) -> QrzLogbookResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let logids = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            logids,
+        })
+    }
+
+    /// QRZ logid of an N1MM contact
+    pub fn get(&self, contact_id: &str) -> Option<u64> {
+        self.logids.get(contact_id).copied()
+    }
+
+    /// Number of tracked contacts
+    pub fn len(&self) -> usize {
+        self.logids.len()
+    }
+
+    /// Whether no contacts are tracked
+    pub fn is_empty(&self) -> bool {
+        self.logids.is_empty()
+    }
+
+    /// Record the QRZ logid of an N1MM contact
+    pub fn insert(&mut self, contact_id: &str, logid: u64) -> QrzLogbookResult<()> {
+        self.logids.insert(contact_id.to_string(), logid);
+        self.save()
+    }
+
+    /// Forget an N1MM contact, returning its QRZ logid
+    pub fn remove(&mut self, contact_id: &str) -> QrzLogbookResult<Option<u64>> {
+        let logid = self.logids.remove(contact_id);
+        if logid.is_some() {
+            self.save()?;
+        }
+        Ok(logid)
+    }
+
+    fn save(&self) -> QrzLogbookResult<()> {
+        if let Some(ref path) = self.path {
+            std::fs::write(path, serde_json::to_string_pretty(&self.logids)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Logbook operation performed for an N1MM event
+#[derive(Debug, Clone, PartialEq)]
+pub enum N1mmAction {
+    /// New contact inserted
+    Inserted { contact_id: String, logid: u64 },
+    /// Edited contact replaced
+    ///
+    /// If QRZ assigned a new logid, `previous_logid` is the old record, which
+    /// was deleted unless `delete_error` is set.
+    Replaced {
+        contact_id: String,
+        logid: u64,
+        previous_logid: Option<u64>,
+        delete_error: Option<String>,
+    },
+    /// Contact deleted from the logbook
+    Deleted { contact_id: String, logid: u64 },
+    /// Event that needs no logbook change
+    Ignored,
+}
+
+/// Listener receiving N1MM Logger+ contact broadcasts
+pub struct N1mmListener {
+    socket: UdpSocket,
+    contacts: ContactMap,
+}
+
+impl N1mmListener {
+    /// Bind a UDP socket for N1MM broadcasts (N1MM uses port 12060 by default)
+    pub async fn bind(addr: impl ToSocketAddrs, contacts: ContactMap) -> QrzLogbookResult<Self> {
+        let socket = UdpSocket::bind(addr).await?;
+        Ok(Self { socket, contacts })
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> QrzLogbookResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// N1MM contact ID to QRZ logid mapping
+    pub fn contacts(&self) -> &ContactMap {
+        &self.contacts
+    }
+
+    /// Receive and parse the next packet
+    pub async fn recv(&self) -> QrzLogbookResult<N1mmEvent> {
+        let mut buf = vec![0u8; 65536];
+        let (len, _) = self.socket.recv_from(&mut buf).await?;
+        let xml = std::str::from_utf8(&buf[..len])
+            .map_err(|_| QrzLogbookError::protocol("N1MM packet is not valid UTF-8"))?;
+        N1mmEvent::parse(xml)
+    }
+
+    /// Apply an event to the logbook
+    ///
+    /// Contacts relayed from other networked N1MM computers are ignored, as
+    /// their originating computer broadcasts them as well.
+    pub async fn handle(
+        &mut self,
+        client: &QrzLogbookClient,
+        event: &N1mmEvent,
+    ) -> QrzLogbookResult<N1mmAction> {
+        match event {
+            N1mmEvent::ContactInfo(contact) if contact.is_original => {
+                let qso = contact.to_qso_record()?;
+                let response = client.insert_qso(&qso, false).await?;
+                self.contacts.insert(&contact.id, response.logid)?;
+                Ok(N1mmAction::Inserted {
+                    contact_id: contact.id.clone(),
+                    logid: response.logid,
+                })
+            }
+            N1mmEvent::ContactReplace(contact) if contact.is_original => {
+                let qso = contact.to_qso_record()?;
+                let response = client.insert_qso(&qso, true).await?;
+
+                // QRZ only replaces records matching call, date, time, band and
+                // mode; an edit to any of those creates a new record instead.
+                // Track the new record before removing the old one so a failed
+                // delete can't orphan it.
+                let previous_logid = self
+                    .contacts
+                    .get(&contact.id)
+                    .filter(|&previous| previous != response.logid);
+                self.contacts.insert(&contact.id, response.logid)?;
+
+                let mut delete_error = None;
+                if let Some(previous) = previous_logid {
+                    if let Err(e) = client.delete_qsos(vec![previous]).await {
+                        delete_error = Some(e.to_string());
+                    }
+                }
+
+                Ok(N1mmAction::Replaced {
+                    contact_id: contact.id.clone(),
+                    logid: response.logid,
+                    previous_logid,
+                    delete_error,
+                })
+            }
+            N1mmEvent::ContactDelete(delete) => match self.contacts.get(&delete.id) {
+                Some(logid) => {
+                    client.delete_qsos(vec![logid]).await?;
+                    self.contacts.remove(&delete.id)?;
+                    Ok(N1mmAction::Deleted {
+                        contact_id: delete.id.clone(),
+                        logid,
+                    })
+                }
+                None => Ok(N1mmAction::Ignored),
+            },
+            _ => Ok(N1mmAction::Ignored),
+        }
+    }
+
+    /// Apply every received event until a socket error occurs
+    ///
+    /// `on_event` is called with the outcome of each contact event; other
+    /// packets and packets that can't be parsed are skipped.
+    pub async fn run<F>(
+        &mut self,
+        client: &QrzLogbookClient,
+        mut on_event: F,
+    ) -> QrzLogbookResult<()>
+    where
+        F: FnMut(QrzLogbookResult<N1mmAction>),
+    {
+        loop {
+            let event = match self.recv().await {
+                Ok(N1mmEvent::Other(_)) | Err(QrzLogbookError::Protocol(_)) => continue,
+                Ok(event) => event,
+                Err(e) => return Err(e),
+            };
+            on_event(self.handle(client, &event).await);
+        }
+    }
+}
+
+fn required(fields: &HashMap<String, String>, name: &str) -> QrzLogbookResult<String> {
+    fields
+        .get(name)
+        .filter(|s| !s.is_empty())
+        .cloned()
+        .ok_or_else(|| QrzLogbookError::protocol(format!("Missing {} in N1MM packet", name)))
+}
+
+fn parse_timestamp(timestamp: &str) -> QrzLogbookResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map_err(|_| QrzLogbookError::protocol(format!("Invalid N1MM timestamp: {}", timestamp)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use chrono::{NaiveDate, NaiveTime};
+
+    const CONTACT_INFO: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<contactinfo>
+    <app>N1MM</app>
+    <contestname>CQWWCW</contestname>
+    <contestnr>73</contestnr>
+    <timestamp>2024-11-23 16:43:38</timestamp>
+    <mycall>K1ABC</mycall>
+    <band>14</band>
+    <rxfreq>1402510</rxfreq>
+    <txfreq>1402510</txfreq>
+    <operator>K1ABC</operator>
+    <mode>CW</mode>
+    <call>JA1XY</call>
+    <snt>599</snt>
+    <sntnr>0</sntnr>
+    <rcv>599</rcv>
+    <rcvnr>0</rcvnr>
+    <gridsquare></gridsquare>
+    <exchange1>25</exchange1>
+    <section></section>
+    <comment></comment>
+    <name></name>
+    <power></power>
+    <ID>f9ff0c0b46ea4b7ca5e9a5e2bb5b3c1d</ID>
+    <IsOriginal>True</IsOriginal>
+    <NetBiosName>CONTEST-PC</NetBiosName>
+    <IsRunQSO>1</IsRunQSO>
+    <StationName>CONTEST-PC</StationName>
+</contactinfo>"#;
+
+    #[test]
+    fn test_parse_contact_info() {
+        let N1mmEvent::ContactInfo(contact) = N1mmEvent::parse(CONTACT_INFO).unwrap() else {
+            panic!("Expected contactinfo");
+        };
+        assert_eq!(contact.id, "f9ff0c0b46ea4b7ca5e9a5e2bb5b3c1d");
+        assert_eq!(contact.tx_freq, Some(14_025_100));
+        assert!(contact.is_original);
+
+        let qso = contact.to_qso_record().unwrap();
+        assert_eq!(qso.call, "JA1XY");
+        assert_eq!(qso.station_callsign, "K1ABC");
+        assert_eq!(qso.qso_date, NaiveDate::from_ymd_opt(2024, 11, 23).unwrap());
+        assert_eq!(qso.time_on, NaiveTime::from_hms_opt(16, 43, 38).unwrap());
+        assert_eq!(qso.band, "20m");
+        assert_eq!(qso.mode, "CW");
+        assert_eq!(qso.freq, Some(14.0251));
+        assert_eq!(qso.rst_sent.as_deref(), Some("599"));
+        assert_eq!(qso.srx_string.as_deref(), Some("25"));
+        assert_eq!(qso.contest_id.as_deref(), Some("CQWWCW"));
+        assert_eq!(qso.srx, None);
+    }
+
+    #[test]
+    fn test_parse_contact_replace_sideband() {
+        let xml = CONTACT_INFO
+            .replace("contactinfo>", "contactreplace>")
+            .replace("<mode>CW</mode>", "<mode>USB</mode>")
+            .replace("<txfreq>1402510</txfreq>", "<txfreq>0</txfreq>");
+        let N1mmEvent::ContactReplace(contact) = N1mmEvent::parse(&xml).unwrap() else {
+            panic!("Expected contactreplace");
+        };
+
+        let qso = contact.to_qso_record().unwrap();
+        assert_eq!(qso.mode, "SSB");
+        assert_eq!(
+            qso.additional_fields.get("submode").map(|s| s.as_str()),
+            Some("USB")
+        );
+        assert_eq!(qso.band, "20m");
+        assert_eq!(qso.freq, None);
+    }
+
+    #[test]
+    fn test_parse_contact_delete() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<contactdelete>
+    <app>N1MM</app>
+    <timestamp>2024-11-23 16:43:38</timestamp>
+    <call>JA1XY</call>
+    <contestnr>73</contestnr>
+    <StationName>CONTEST-PC</StationName>
+    <ID>f9ff0c0b46ea4b7ca5e9a5e2bb5b3c1d</ID>
+</contactdelete>"#;
+
+        let N1mmEvent::ContactDelete(delete) = N1mmEvent::parse(xml).unwrap() else {
+            panic!("Expected contactdelete");
+        };
+        assert_eq!(delete.id, "f9ff0c0b46ea4b7ca5e9a5e2bb5b3c1d");
+        assert_eq!(delete.call, "JA1XY");
+    }
+
+    #[test]
+    fn test_parse_other_and_invalid_packets() {
+        assert_eq!(
+            N1mmEvent::parse("<RadioInfo><app>N1MM</app></RadioInfo>").unwrap(),
+            N1mmEvent::Other("radioinfo".to_string())
+        );
+        assert!(matches!(
+            N1mmEvent::parse("<contactinfo><call>W1AW</call>"),
+            Err(QrzLogbookError::Protocol(_))
+        ));
+        assert!(matches!(
+            N1mmEvent::parse("<contactinfo><call>W1AW</call></contactinfo>"),
+            Err(QrzLogbookError::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn test_contact_map_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("contacts.json");
+
+        let mut map = ContactMap::load(&path).unwrap();
+        assert!(map.is_empty());
+        map.insert("abc", 12345).unwrap();
+        map.insert("def", 12346).unwrap();
+        assert_eq!(map.remove("def").unwrap(), Some(12346));
+
+        let reloaded = ContactMap::load(&path).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.get("abc"), Some(12345));
+    }
+
+    #[tokio::test]
+    async fn test_listener_receives_contacts() {
+        let listener = N1mmListener::bind("127.0.0.1:0", ContactMap::new())
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sender.send_to(CONTACT_INFO.as_bytes(), addr).await.unwrap();
+
+        let event = listener.recv().await.unwrap();
+        assert!(matches!(event, N1mmEvent::ContactInfo(ref c) if c.call == "JA1XY"));
+    }
+
+    async fn listener() -> N1mmListener {
+        N1mmListener::bind("127.0.0.1:0", ContactMap::new())
+            .await
+            .unwrap()
+    }
+
+    fn event(xml: &str) -> N1mmEvent {
+        N1mmEvent::parse(xml).unwrap()
+    }
+
+    const ID: &str = "f9ff0c0b46ea4b7ca5e9a5e2bb5b3c1d";
+
+    #[tokio::test]
+    async fn test_replace_keeps_new_logid_when_delete_fails() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&LOGID=101&COUNT=1",
+            "RESULT=OK&LOGID=102&COUNT=1",
+            "RESULT=FAIL&REASON=delete%20failed",
+        ]);
+        let client = QrzLogbookClient::with_transport(
+            "ABCD-1234-EF56-7890",
+            "TestApp/1.0.0 (N0CALL)",
+            transport.clone(),
+        )
+        .unwrap();
+        let mut listener = listener().await;
+
+        listener
+            .handle(&client, &event(CONTACT_INFO))
+            .await
+            .unwrap();
+        let replace = CONTACT_INFO
+            .replace("contactinfo>", "contactreplace>")
+            .replace("1402510", "702510");
+        let action = listener.handle(&client, &event(&replace)).await.unwrap();
+
+        let N1mmAction::Replaced {
+            logid,
+            previous_logid,
+            delete_error,
+            ..
+        } = action
+        else {
+            panic!("Expected Replaced, got {:?}", action);
+        };
+        assert_eq!(logid, 102);
+        assert_eq!(previous_logid, Some(101));
+        assert!(delete_error.unwrap().contains("delete failed"));
+        assert_eq!(listener.contacts().get(ID), Some(102));
+        assert_eq!(transport.requests()[2].param("LOGIDS"), Some("101"));
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_insert_replace_delete_against_fake_logbook() {
+        use crate::testing::FakeLogbook;
+
+        let logbook = FakeLogbook::new("K1ABC", "ABCD-1234-EF56-7890");
+        let client = QrzLogbookClient::with_transport(
+            "ABCD-1234-EF56-7890",
+            "TestApp/1.0.0 (K1ABC)",
+            logbook.clone(),
+        )
+        .unwrap();
+        let mut listener = listener().await;
+
+        let N1mmAction::Inserted { logid, .. } = listener
+            .handle(&client, &event(CONTACT_INFO))
+            .await
+            .unwrap()
+        else {
+            panic!("Expected Inserted");
+        };
+
+        // Editing the exchange replaces the record in place
+        let edit = CONTACT_INFO
+            .replace("contactinfo>", "contactreplace>")
+            .replace("<exchange1>25</exchange1>", "<exchange1>26</exchange1>");
+        let action = listener.handle(&client, &event(&edit)).await.unwrap();
+        assert_eq!(
+            action,
+            N1mmAction::Replaced {
+                contact_id: ID.to_string(),
+                logid,
+                previous_logid: None,
+                delete_error: None,
+            }
+        );
+        assert_eq!(
+            logbook.get(logid).unwrap().qso.srx_string.as_deref(),
+            Some("26")
+        );
+
+        // Changing the band creates a new record and removes the old one
+        let rebanded = edit.replace("1402510", "702510");
+        let N1mmAction::Replaced {
+            logid: new_logid,
+            previous_logid,
+            delete_error,
+            ..
+        } = listener.handle(&client, &event(&rebanded)).await.unwrap()
+        else {
+            panic!("Expected Replaced");
+        };
+        assert_ne!(new_logid, logid);
+        assert_eq!(previous_logid, Some(logid));
+        assert_eq!(delete_error, None);
+        assert_eq!(logbook.len(), 1);
+        assert_eq!(logbook.get(new_logid).unwrap().qso.band, "40m");
+
+        let delete = N1mmEvent::ContactDelete(N1mmContactDelete {
+            id: ID.to_string(),
+            call: "JA1XY".to_string(),
+            timestamp: None,
+        });
+        assert_eq!(
+            listener.handle(&client, &delete).await.unwrap(),
+            N1mmAction::Deleted {
+                contact_id: ID.to_string(),
+                logid: new_logid,
+            }
+        );
+        assert!(logbook.is_empty());
+        assert!(listener.contacts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_ignores_relayed_contacts() {
+        let client =
+            QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestApp/1.0.0 (N0CALL)").unwrap();
+        let mut listener = N1mmListener::bind("127.0.0.1:0", ContactMap::new())
+            .await
+            .unwrap();
+
+        let relayed = CONTACT_INFO.replace("<IsOriginal>True", "<IsOriginal>False");
+        let event = N1mmEvent::parse(&relayed).unwrap();
+        assert_eq!(
+            listener.handle(&client, &event).await.unwrap(),
+            N1mmAction::Ignored
+        );
+
+        let unknown_delete = N1mmEvent::ContactDelete(N1mmContactDelete {
+            id: "unknown".to_string(),
+            call: "JA1XY".to_string(),
+            timestamp: None,
+        });
+        assert_eq!(
+            listener.handle(&client, &unknown_delete).await.unwrap(),
+            N1mmAction::Ignored
+        );
+    }
+}
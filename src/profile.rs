@@ -0,0 +1,262 @@
+use crate::{
+    error::{QrzLogbookError, QrzLogbookResult},
+    secret::ApiKey,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Logbook profile mapping a station callsign to its QRZ credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogbookProfile {
+    /// Station callsign this logbook belongs to
+    pub callsign: String,
+    /// QRZ API access key for this logbook
+    pub api_key: ApiKey,
+    /// User agent for this logbook (falls back to the config default)
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
+/// Profile configuration, usually loaded from a TOML file
+///
+/// # Example
+/// ```toml
+/// default_user_agent = "ClubUploader/1.0.0 (W1AW)"
+/// default_profile = "W1AW"
+///
+/// [[profiles]]
+/// callsign = "W1AW"
+/// api_key = "AAAA-BBBB-CCCC-DDDD"
+///
+/// [[profiles]]
+/// callsign = "K1ABC"
+/// api_key = "EEEE-FFFF-0000-1111"
+/// user_agent = "ContestLogger/2.0.0 (K1ABC)"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// User agent used by profiles that don't set their own
+    #[serde(default)]
+    pub default_user_agent: Option<String>,
+    /// Callsign of the profile used when no other profile matches
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    /// Configured logbook profiles
+    #[serde(default)]
+    pub profiles: Vec<LogbookProfile>,
+}
+
+impl ProfileConfig {
+    /// Parse a profile configuration from a TOML string
+    pub fn from_toml_str(toml: &str) -> QrzLogbookResult<Self> {
+        let config: Self = toml::from_str(toml)
+            .map_err(|e| QrzLogbookError::config(format!("Invalid profile config: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load a profile configuration from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> QrzLogbookResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            QrzLogbookError::config(format!("Unable to read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml_str(&contents)
+    }
+
+    /// Serialize the configuration back to TOML
+    pub fn to_toml_string(&self) -> QrzLogbookResult<String> {
+        toml::to_string_pretty(self)
+            .map_err(|e| QrzLogbookError::config(format!("Unable to serialize config: {}", e)))
+    }
+
+    /// Find the profile for a station callsign
+    ///
+    /// An exact (case-insensitive) match wins; otherwise the base callsign is
+    /// compared, so `VE3/K1ABC/P` resolves to the `K1ABC` profile.
+    pub fn profile_for(&self, station_callsign: &str) -> Option<&LogbookProfile> {
+        find_by_callsign(
+            self.profiles.iter().map(|p| (p.callsign.as_str(), p)),
+            station_callsign,
+        )
+    }
+
+    /// User agent for a profile, falling back to the configured default
+    pub fn user_agent_for<'a>(&'a self, profile: &'a LogbookProfile) -> Option<&'a str> {
+        profile
+            .user_agent
+            .as_deref()
+            .or(self.default_user_agent.as_deref())
+    }
+
+    fn validate(&self) -> QrzLogbookResult<()> {
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if profile.callsign.trim().is_empty() {
+                return Err(QrzLogbookError::config(format!(
+                    "Profile {} has an empty callsign",
+                    i + 1
+                )));
+            }
+
+            if self.user_agent_for(profile).is_none() {
+                return Err(QrzLogbookError::config(format!(
+                    "Profile {} has no user_agent and no default_user_agent is set",
+                    profile.callsign
+                )));
+            }
+
+            let duplicate = self.profiles[..i]
+                .iter()
+                .any(|p| p.callsign.eq_ignore_ascii_case(&profile.callsign));
+            if duplicate {
+                return Err(QrzLogbookError::config(format!(
+                    "Duplicate profile for {}",
+                    profile.callsign
+                )));
+            }
+        }
+
+        if let Some(ref default) = self.default_profile {
+            if !self
+                .profiles
+                .iter()
+                .any(|p| p.callsign.eq_ignore_ascii_case(default))
+            {
+                return Err(QrzLogbookError::config(format!(
+                    "Default profile {} is not configured",
+                    default
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the item registered for a station callsign
+///
+/// An exact (case-insensitive) match wins; otherwise the first item with the
+/// same base callsign is returned.
+pub(crate) fn find_by_callsign<'a, T>(
+    items: impl IntoIterator<Item = (&'a str, T)>,
+    station_callsign: &str,
+) -> Option<T> {
+    let station_callsign = station_callsign.trim();
+    let base = base_callsign(station_callsign);
+    let mut base_match = None;
+
+    for (callsign, item) in items {
+        if callsign.eq_ignore_ascii_case(station_callsign) {
+            return Some(item);
+        }
+        if base_match.is_none() && base_callsign(callsign).eq_ignore_ascii_case(base) {
+            base_match = Some(item);
+        }
+    }
+
+    base_match
+}
+
+/// Strip portable prefixes and suffixes from a callsign (`VE3/K1ABC/P` -> `K1ABC`)
+///
+/// The base is the part with a digit followed by letters, as in `N1A/VE3`
+/// -> `N1A`; if several parts qualify, the longest one wins.
+pub(crate) fn base_callsign(callsign: &str) -> &str {
+    callsign
+        .trim()
+        .split('/')
+        .max_by_key(|part| (has_callsign_suffix(part), part.len()))
+        .unwrap_or_default()
+}
+
+/// Whether a callsign part has a digit followed by letters (`K1ABC`, `N1A`)
+fn has_callsign_suffix(part: &str) -> bool {
+    let stem = part.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    stem.len() < part.len() && stem.ends_with(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+default_user_agent = "ClubUploader/1.0.0 (W1AW)"
+default_profile = "W1AW"
+
+[[profiles]]
+callsign = "W1AW"
+api_key = "AAAA-BBBB-CCCC-DDDD"
+
+[[profiles]]
+callsign = "K1ABC"
+api_key = "EEEE-FFFF-0000-1111"
+user_agent = "ContestLogger/2.0.0 (K1ABC)"
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        let config = ProfileConfig::from_toml_str(CONFIG).unwrap();
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.default_profile.as_deref(), Some("W1AW"));
+
+        let k1abc = config.profile_for("k1abc").unwrap();
+        assert_eq!(k1abc.api_key.expose(), "EEEE-FFFF-0000-1111");
+        assert!(!format!("{:?}", k1abc).contains("EEEE-FFFF"));
+        assert_eq!(
+            config.user_agent_for(k1abc),
+            Some("ContestLogger/2.0.0 (K1ABC)")
+        );
+
+        let w1aw = config.profile_for("W1AW").unwrap();
+        assert_eq!(
+            config.user_agent_for(w1aw),
+            Some("ClubUploader/1.0.0 (W1AW)")
+        );
+    }
+
+    #[test]
+    fn test_profile_for_portable_callsign() {
+        let config = ProfileConfig::from_toml_str(CONFIG).unwrap();
+        assert_eq!(config.profile_for("VE3/K1ABC/P").unwrap().callsign, "K1ABC");
+        assert!(config.profile_for("N0CALL").is_none());
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let missing_agent = r#"
+[[profiles]]
+callsign = "W1AW"
+api_key = "AAAA-BBBB-CCCC-DDDD"
+"#;
+        assert!(matches!(
+            ProfileConfig::from_toml_str(missing_agent),
+            Err(QrzLogbookError::Config(_))
+        ));
+
+        let bad_default = r#"
+default_user_agent = "ClubUploader/1.0.0 (W1AW)"
+default_profile = "K1ABC"
+
+[[profiles]]
+callsign = "W1AW"
+api_key = "AAAA-BBBB-CCCC-DDDD"
+"#;
+        assert!(matches!(
+            ProfileConfig::from_toml_str(bad_default),
+            Err(QrzLogbookError::Config(_))
+        ));
+
+        assert!(ProfileConfig::from_toml_str("profiles = 5").is_err());
+    }
+
+    #[test]
+    fn test_base_callsign() {
+        assert_eq!(base_callsign("K1ABC"), "K1ABC");
+        assert_eq!(base_callsign("K1ABC/P"), "K1ABC");
+        assert_eq!(base_callsign("VE3/K1ABC"), "K1ABC");
+        assert_eq!(base_callsign("VE3/K1ABC/MM"), "K1ABC");
+        assert_eq!(base_callsign("N1A/VE3"), "N1A");
+        assert_eq!(base_callsign("VE3/N1A"), "N1A");
+        assert_eq!(base_callsign("W1AW/QRP"), "W1AW");
+    }
+}
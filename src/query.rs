@@ -0,0 +1,812 @@
+//! Client-side QSO filter expressions
+//!
+//! A [`QsoFilter`] is built in code or parsed from a small query syntax:
+//!
+//! ```text
+//! band=20m and mode in (CW,FT8) and dxcc!=291 and date>=2024-01-01
+//! ```
+//!
+//! Field names are ADIF names (`call`, `band`, `gridsquare`, ...), plus the
+//! aliases `date` for `qso_date` and `time` for `time_on`. Comparisons are
+//! `=`, `!=`, `<`, `<=`, `>`, `>=`, `~` (contains) and `in (...)`, joined with
+//! `and`, `or`, `not` and parentheses. Values containing spaces or operator
+//! characters can be quoted.
+//!
+//! Dates and times compare chronologically (`2024-01-01` or `20240101`,
+//! `14:30` or `1430`), numbers numerically and text case-insensitively.
+//!
+//! [`QsoFilter::plan`] splits a filter into the conditions the FETCH action
+//! can apply and the rest, which
+//! [`QrzLogbookClient::fetch_matching`](crate::QrzLogbookClient::fetch_matching)
+//! applies locally.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::query::QsoFilter;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! # let client = qrz_logbook_api::QrzLogbookClient::new("ABCD-1234-EF56-7890", "agent")?;
+//! let filter: QsoFilter = "band=20m and mode in (CW,FT8) and dxcc!=291".parse()?;
+//! let qsos = client.fetch_matching(&filter).await?;
+//!
+//! // Filters also work on records already in memory
+//! let cw: Vec<_> = qsos.iter().filter(|q| filter.matches(q)).collect();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::{parse_date, parse_time, AdifParser},
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::{FetchOptions, QsoRecord},
+};
+use chrono::{NaiveDate, NaiveTime};
+use std::{cmp::Ordering, collections::HashMap, fmt, str::FromStr};
+
+/// Comparison operator in a [`QsoFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match
+    Contains,
+}
+
+impl CompareOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Contains => "~",
+        }
+    }
+}
+
+/// Filter expression evaluated against [`QsoRecord`]s
+#[derive(Debug, Clone, PartialEq)]
+pub enum QsoFilter {
+    /// Compare an ADIF field with a value
+    Compare {
+        field: String,
+        op: CompareOp,
+        value: String,
+    },
+    /// Field equals one of the values
+    In {
+        field: String,
+        values: Vec<String>,
+    },
+    And(Vec<QsoFilter>),
+    Or(Vec<QsoFilter>),
+    Not(Box<QsoFilter>),
+}
+
+/// A filter split into FETCH options and the conditions left to apply locally
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub options: FetchOptions,
+    /// Conditions the server cannot apply, if any
+    pub residual: Option<QsoFilter>,
+}
+
+impl QsoFilter {
+    /// Compare an ADIF field (or `date`/`time` alias) with a value
+    pub fn compare(field: impl Into<String>, op: CompareOp, value: impl Into<String>) -> Self {
+        QsoFilter::Compare {
+            field: normalize_field(&field.into()),
+            op,
+            value: value.into(),
+        }
+    }
+
+    /// Field equals one of the values
+    pub fn one_of<I, S>(field: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        QsoFilter::In {
+            field: normalize_field(&field.into()),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Both filters must match
+    pub fn and(self, other: QsoFilter) -> Self {
+        match self {
+            QsoFilter::And(mut filters) => {
+                filters.push(other);
+                QsoFilter::And(filters)
+            }
+            filter => QsoFilter::And(vec![filter, other]),
+        }
+    }
+
+    /// Either filter must match
+    pub fn or(self, other: QsoFilter) -> Self {
+        match self {
+            QsoFilter::Or(mut filters) => {
+                filters.push(other);
+                QsoFilter::Or(filters)
+            }
+            filter => QsoFilter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Parse a filter from the query syntax
+    pub fn parse(query: &str) -> QrzLogbookResult<Self> {
+        let tokens = tokenize(query)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let filter = parser.or_expr()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(query_error(format!("unexpected {}", token))),
+        }
+    }
+
+    /// Check whether a QSO record matches the filter
+    pub fn matches(&self, qso: &QsoRecord) -> bool {
+        let fields: HashMap<String, String> = AdifParser::to_fields(qso).into_iter().collect();
+        self.eval(&fields)
+    }
+
+    fn eval(&self, fields: &HashMap<String, String>) -> bool {
+        match self {
+            QsoFilter::Compare { field, op, value } => {
+                let Some(actual) = fields.get(field) else {
+                    return *op == CompareOp::Ne;
+                };
+                if *op == CompareOp::Contains {
+                    return actual.to_uppercase().contains(&value.to_uppercase());
+                }
+                match compare_values(field, actual, value) {
+                    Some(ordering) => match op {
+                        CompareOp::Eq => ordering == Ordering::Equal,
+                        CompareOp::Ne => ordering != Ordering::Equal,
+                        CompareOp::Lt => ordering == Ordering::Less,
+                        CompareOp::Le => ordering != Ordering::Greater,
+                        CompareOp::Gt => ordering == Ordering::Greater,
+                        CompareOp::Ge => ordering != Ordering::Less,
+                        CompareOp::Contains => unreachable!(),
+                    },
+                    // Values that cannot be compared only differ
+                    None => *op == CompareOp::Ne,
+                }
+            }
+            QsoFilter::In { field, values } => fields.get(field).is_some_and(|actual| {
+                values
+                    .iter()
+                    .any(|v| compare_values(field, actual, v) == Some(Ordering::Equal))
+            }),
+            QsoFilter::And(filters) => filters.iter().all(|f| f.eval(fields)),
+            QsoFilter::Or(filters) => filters.iter().any(|f| f.eval(fields)),
+            QsoFilter::Not(filter) => !filter.eval(fields),
+        }
+    }
+
+    /// Split the filter into FETCH options and a residual local filter
+    ///
+    /// Only top-level `and` conditions are pushed down: equality on `band`,
+    /// `mode`, `call` and `dxcc`, and bounds on `qso_date`. Everything else
+    /// stays in the residual filter.
+    pub fn plan(&self) -> QueryPlan {
+        let conditions = match self {
+            QsoFilter::And(filters) => filters.clone(),
+            filter => vec![filter.clone()],
+        };
+
+        let mut options = FetchOptions::new();
+        let mut residual = Vec::new();
+        for condition in conditions {
+            if !push_down(&condition, &mut options) {
+                residual.push(condition);
+            }
+        }
+
+        let pushed = options.band.is_some()
+            || options.mode.is_some()
+            || options.call.is_some()
+            || options.dxcc.is_some()
+            || options.date_from.is_some()
+            || options.date_to.is_some();
+        if !pushed {
+            options.all = true;
+        }
+
+        let residual = match residual.len() {
+            0 => None,
+            1 => residual.pop(),
+            _ => Some(QsoFilter::And(residual)),
+        };
+
+        QueryPlan { options, residual }
+    }
+}
+
+impl std::ops::Not for QsoFilter {
+    type Output = QsoFilter;
+
+    fn not(self) -> Self::Output {
+        QsoFilter::Not(Box::new(self))
+    }
+}
+
+impl FromStr for QsoFilter {
+    type Err = QrzLogbookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QsoFilter::parse(s)
+    }
+}
+
+impl fmt::Display for QsoFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join(f: &mut fmt::Formatter<'_>, filters: &[QsoFilter], sep: &str) -> fmt::Result {
+            for (i, filter) in filters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", sep)?;
+                }
+                match filter {
+                    QsoFilter::And(_) | QsoFilter::Or(_) => write!(f, "({})", filter)?,
+                    _ => write!(f, "{}", filter)?,
+                }
+            }
+            Ok(())
+        }
+
+        match self {
+            QsoFilter::Compare { field, op, value } => {
+                write!(f, "{}{}{}", field, op.as_str(), quote(value))
+            }
+            QsoFilter::In { field, values } => {
+                let values: Vec<_> = values.iter().map(|v| quote(v)).collect();
+                write!(f, "{} in ({})", field, values.join(","))
+            }
+            QsoFilter::And(filters) => join(f, filters, "and"),
+            QsoFilter::Or(filters) => join(f, filters, "or"),
+            QsoFilter::Not(filter) => write!(f, "not ({})", filter),
+        }
+    }
+}
+
+fn quote(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(is_word_char) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('"', "\\\""))
+    }
+}
+
+fn normalize_field(field: &str) -> String {
+    match field.to_lowercase().as_str() {
+        "date" => "qso_date".to_string(),
+        "time" => "time_on".to_string(),
+        field => field.to_string(),
+    }
+}
+
+fn is_date_field(field: &str) -> bool {
+    matches!(field, "qso_date" | "qso_date_off" | "qslsdate" | "qslrdate")
+}
+
+fn is_time_field(field: &str) -> bool {
+    matches!(field, "time_on" | "time_off")
+}
+
+fn parse_query_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .or_else(|| parse_date(value).ok())
+}
+
+fn parse_query_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok()
+        .or_else(|| parse_time(value).ok())
+}
+
+/// Compare an ADIF-formatted field value with a query value
+fn compare_values(field: &str, actual: &str, value: &str) -> Option<Ordering> {
+    if is_date_field(field) {
+        return Some(parse_date(actual).ok()?.cmp(&parse_query_date(value)?));
+    }
+
+    if is_time_field(field) {
+        return Some(parse_time(actual).ok()?.cmp(&parse_query_time(value)?));
+    }
+
+    if let (Ok(a), Ok(b)) = (actual.trim().parse::<f64>(), value.trim().parse::<f64>()) {
+        return a.partial_cmp(&b);
+    }
+
+    Some(actual.to_uppercase().cmp(&value.to_uppercase()))
+}
+
+/// Move a condition into the FETCH options, if the server can apply it
+fn push_down(condition: &QsoFilter, options: &mut FetchOptions) -> bool {
+    let QsoFilter::Compare { field, op, value } = condition else {
+        return false;
+    };
+
+    match (field.as_str(), op) {
+        ("band", CompareOp::Eq) if options.band.is_none() => {
+            options.band = Some(value.clone());
+            true
+        }
+        ("mode", CompareOp::Eq) if options.mode.is_none() => {
+            options.mode = Some(value.clone());
+            true
+        }
+        ("call", CompareOp::Eq) if options.call.is_none() => {
+            options.call = Some(value.clone());
+            true
+        }
+        ("dxcc", CompareOp::Eq) if options.dxcc.is_none() => match value.trim().parse() {
+            Ok(dxcc) => {
+                options.dxcc = Some(dxcc);
+                true
+            }
+            Err(_) => false,
+        },
+        ("qso_date", _) => {
+            let Some(date) = parse_query_date(value) else {
+                return false;
+            };
+            let (from, to) = match op {
+                CompareOp::Eq => (Some(date), Some(date)),
+                CompareOp::Ge => (Some(date), None),
+                CompareOp::Le => (None, Some(date)),
+                // No day after MAX or before MIN: leave these to the client
+                CompareOp::Gt => match date.succ_opt() {
+                    Some(next) => (Some(next), None),
+                    None => return false,
+                },
+                CompareOp::Lt => match date.pred_opt() {
+                    Some(previous) => (None, Some(previous)),
+                    None => return false,
+                },
+                _ => return false,
+            };
+            if (from.is_some() && options.date_from.is_some())
+                || (to.is_some() && options.date_to.is_some())
+            {
+                return false;
+            }
+            options.date_from = from.or(options.date_from);
+            options.date_to = to.or(options.date_to);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn query_error(message: impl fmt::Display) -> QrzLogbookError {
+    QrzLogbookError::invalid_params(format!("Invalid query: {}", message))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(value) => write!(f, "\"{}\"", value),
+            Token::Op(op) => write!(f, "'{}'", op.as_str()),
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':' | '+' | '#')
+}
+
+fn tokenize(query: &str) -> QrzLogbookResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '~' => {
+                chars.next();
+                tokens.push(Token::Op(if c == '=' {
+                    CompareOp::Eq
+                } else {
+                    CompareOp::Contains
+                }));
+            }
+            '!' | '<' | '>' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                tokens.push(Token::Op(match (c, eq) {
+                    ('!', true) => CompareOp::Ne,
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    _ => return Err(query_error("expected '!='")),
+                }));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some(q) if q == c => break,
+                        Some(ch) => value.push(ch),
+                        None => return Err(query_error("unterminated string")),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(ch) = chars.next_if(|&ch| is_word_char(ch)) {
+                    word.push(ch);
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => return Err(query_error(format!("unexpected character '{}'", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Maximum nesting of `not` and parentheses, to bound parser recursion
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> QrzLogbookResult<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(query_error(format!(
+                "expected {}, found {}",
+                expected, token
+            ))),
+            None => Err(query_error(format!("expected {}", expected))),
+        }
+    }
+
+    fn or_expr(&mut self) -> QrzLogbookResult<QsoFilter> {
+        let mut filters = vec![self.and_expr()?];
+        while self.keyword("or") {
+            filters.push(self.and_expr()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            QsoFilter::Or(filters)
+        })
+    }
+
+    fn and_expr(&mut self) -> QrzLogbookResult<QsoFilter> {
+        let mut filters = vec![self.unary()?];
+        while self.keyword("and") {
+            filters.push(self.unary()?);
+        }
+        Ok(if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            QsoFilter::And(filters)
+        })
+    }
+
+    fn unary(&mut self) -> QrzLogbookResult<QsoFilter> {
+        if self.depth >= MAX_DEPTH {
+            return Err(query_error("query is nested too deeply"));
+        }
+        self.depth += 1;
+        let filter = self.negation_or_group();
+        self.depth -= 1;
+        filter
+    }
+
+    fn negation_or_group(&mut self) -> QrzLogbookResult<QsoFilter> {
+        if self.keyword("not") {
+            return Ok(!self.unary()?);
+        }
+
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let filter = self.or_expr()?;
+            self.expect(Token::RParen)?;
+            return Ok(filter);
+        }
+
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> QrzLogbookResult<QsoFilter> {
+        let field = match self.next() {
+            Some(Token::Word(word)) => normalize_field(&word),
+            Some(token) => return Err(query_error(format!("expected field, found {}", token))),
+            None => return Err(query_error("expected field")),
+        };
+
+        let negated = self.keyword("not");
+        if self.keyword("in") {
+            self.expect(Token::LParen)?;
+            let mut values = vec![self.value()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                values.push(self.value()?);
+            }
+            self.expect(Token::RParen)?;
+
+            let filter = QsoFilter::In { field, values };
+            return Ok(if negated { !filter } else { filter });
+        }
+        if negated {
+            return Err(query_error("expected 'in' after 'not'"));
+        }
+
+        match self.next() {
+            Some(Token::Op(op)) => Ok(QsoFilter::Compare {
+                field,
+                op,
+                value: self.value()?,
+            }),
+            Some(token) => Err(query_error(format!(
+                "expected operator after {}, found {}",
+                field, token
+            ))),
+            None => Err(query_error(format!("expected operator after {}", field))),
+        }
+    }
+
+    fn value(&mut self) -> QrzLogbookResult<String> {
+        match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(value),
+            Some(token) => Err(query_error(format!("expected value, found {}", token))),
+            None => Err(query_error("expected value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PropMode;
+
+    fn qso(call: &str, band: &str, mode: &str, date: (i32, u32, u32)) -> QsoRecord {
+        QsoRecord::builder()
+            .call(call)
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band(band)
+            .mode(mode)
+            .build()
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let filter =
+            QsoFilter::parse("band=20m and mode in (CW,FT8) and dxcc!=291 and date>=2024-01-01")
+                .unwrap();
+
+        let mut ja1xy = qso("JA1XY", "20m", "FT8", (2024, 3, 15));
+        ja1xy.dxcc = Some(339);
+        assert!(filter.matches(&ja1xy));
+
+        let mut w1aw = ja1xy.clone();
+        w1aw.dxcc = Some(291);
+        assert!(!filter.matches(&w1aw));
+
+        // A missing field is not equal to anything
+        ja1xy.dxcc = None;
+        assert!(filter.matches(&ja1xy));
+
+        assert!(!filter.matches(&qso("JA1XY", "20M", "ssb", (2024, 3, 15))));
+        assert!(!filter.matches(&qso("JA1XY", "20m", "cw", (2023, 12, 31))));
+        assert!(filter.matches(&qso("JA1XY", "20M", "cw", (2024, 1, 1))));
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut record = qso("DL1AB/P", "40m", "CW", (2024, 6, 8));
+        record.freq = Some(7.0255);
+        record.prop_mode = Some(PropMode::Satellite);
+        record.comment = Some("Portable in the Harz".to_string());
+
+        for query in [
+            "freq>7 and freq<7.3",
+            "freq>=7.0255",
+            "time<=14:30 and time>1400",
+            "call=dl1ab/p",
+            "comment~harz",
+            "not (band=20m or band=15m)",
+            "band not in (20m, 15m)",
+            "prop_mode=SAT",
+            "qso_date=20240608",
+            "gridsquare!=FN42",
+            r#"comment="Portable in the Harz""#,
+        ] {
+            assert!(
+                QsoFilter::parse(query).unwrap().matches(&record),
+                "{}",
+                query
+            );
+        }
+
+        for query in [
+            "freq>10",
+            "gridsquare=FN42",
+            "date<2024-06-08",
+            "mode in (SSB)",
+        ] {
+            assert!(
+                !QsoFilter::parse(query).unwrap().matches(&record),
+                "{}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let filter = QsoFilter::parse("band=20m or band=40m and mode=CW").unwrap();
+        assert!(matches!(filter, QsoFilter::Or(ref f) if f.len() == 2));
+        assert!(filter.matches(&qso("W1AW", "20m", "SSB", (2024, 1, 1))));
+        assert!(!filter.matches(&qso("W1AW", "40m", "SSB", (2024, 1, 1))));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for query in [
+            "",
+            "band",
+            "band=",
+            "band=20m and",
+            "band=20m mode=CW",
+            "(band=20m",
+            "mode in (CW,",
+            "band ! 20m",
+            "comment=\"open",
+            "band=20m;",
+            &format!("{}band=20m{}", "(".repeat(100), ")".repeat(100)),
+            &format!("{}band=20m", "not ".repeat(100)),
+        ] {
+            assert!(
+                matches!(
+                    QsoFilter::parse(query),
+                    Err(QrzLogbookError::InvalidParams(_))
+                ),
+                "{}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        let query = r#"band=20m and (mode=CW or comment~"big gun") and not (dxcc in (291,110))"#;
+        let filter = QsoFilter::parse(query).unwrap();
+        assert_eq!(filter.to_string(), query);
+        assert_eq!(QsoFilter::parse(&filter.to_string()).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_plan_pushdown() {
+        let filter = QsoFilter::parse(
+            "band=20m and mode in (CW,FT8) and dxcc=339 and date>=2024-01-01 and date<2025-01-01",
+        )
+        .unwrap();
+        let plan = filter.plan();
+
+        assert_eq!(plan.options.band.as_deref(), Some("20m"));
+        assert_eq!(plan.options.mode, None);
+        assert_eq!(plan.options.dxcc, Some(339));
+        assert_eq!(plan.options.date_from, NaiveDate::from_ymd_opt(2024, 1, 1));
+        assert_eq!(plan.options.date_to, NaiveDate::from_ymd_opt(2024, 12, 31));
+        assert!(!plan.options.all);
+        assert_eq!(
+            plan.residual,
+            Some(QsoFilter::one_of("mode", ["CW", "FT8"]))
+        );
+    }
+
+    #[test]
+    fn test_date_pushdown_at_limits() {
+        let max = NaiveDate::MAX.format("%Y-%m-%d").to_string();
+        let min = NaiveDate::MIN.format("%Y-%m-%d").to_string();
+        assert_eq!(parse_query_date(&max), Some(NaiveDate::MAX));
+        assert_eq!(parse_query_date(&min), Some(NaiveDate::MIN));
+
+        for filter in [
+            QsoFilter::compare("qso_date", CompareOp::Gt, max),
+            QsoFilter::compare("qso_date", CompareOp::Lt, min),
+        ] {
+            let plan = filter.plan();
+            assert_eq!(plan.options.date_from, None);
+            assert_eq!(plan.options.date_to, None);
+            assert_eq!(plan.residual, Some(filter));
+        }
+
+        let nested = format!("{}band=20m{}", "(".repeat(10), ")".repeat(10));
+        assert!(QsoFilter::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn test_plan_without_pushdown() {
+        let filter = QsoFilter::compare("band", CompareOp::Eq, "20m").or(QsoFilter::compare(
+            "band",
+            CompareOp::Eq,
+            "40m",
+        ));
+        let plan = filter.plan();
+        assert!(plan.options.all);
+        assert_eq!(plan.residual, Some(filter));
+
+        let plan = QsoFilter::compare("call", CompareOp::Eq, "W1AW")
+            .and(QsoFilter::compare("call", CompareOp::Eq, "K1ABC"))
+            .plan();
+        assert_eq!(plan.options.call.as_deref(), Some("W1AW"));
+        assert_eq!(
+            plan.residual,
+            Some(QsoFilter::compare("call", CompareOp::Eq, "K1ABC"))
+        );
+    }
+}
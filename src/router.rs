@@ -0,0 +1,232 @@
+use crate::{
+    client::QrzLogbookClient,
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::{InsertResponse, QsoRecord},
+    profile::{find_by_callsign, ProfileConfig},
+};
+use std::collections::BTreeMap;
+
+/// Routes QSO records to the logbook that owns their station callsign
+///
+/// # Example
+/// ```rust,no_run
+/// use qrz_logbook_api::{LogbookRouter, ProfileConfig};
+///
+/// # async fn example(qsos: Vec<qrz_logbook_api::QsoRecord>) -> Result<(), Box<dyn std::error::Error>> {
+/// let config = ProfileConfig::from_file("logbooks.toml")?;
+/// let router = LogbookRouter::from_config(&config)?;
+///
+/// for qso in &qsos {
+///     let result = router.insert_qso(qso, false).await?;
+///     println!("{} -> logid {}", qso.station_callsign, result.logid);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct LogbookRouter {
+    clients: BTreeMap<String, QrzLogbookClient>,
+    default_callsign: Option<String>,
+}
+
+impl LogbookRouter {
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a router with one client per configured profile
+    pub fn from_config(config: &ProfileConfig) -> QrzLogbookResult<Self> {
+        let mut router = Self::new();
+
+        for profile in &config.profiles {
+            let user_agent = config.user_agent_for(profile).ok_or_else(|| {
+                QrzLogbookError::config(format!("Profile {} has no user agent", profile.callsign))
+            })?;
+            let client = QrzLogbookClient::new(profile.api_key.clone(), user_agent)?;
+            router.add_client(&profile.callsign, client);
+        }
+
+        if let Some(ref default) = config.default_profile {
+            router.set_default(default)?;
+        }
+
+        Ok(router)
+    }
+
+    /// Register the client for a station callsign
+    pub fn add_client(&mut self, callsign: &str, client: QrzLogbookClient) {
+        self.clients.insert(normalize(callsign), client);
+    }
+
+    /// Use the client of `callsign` for QSOs no other profile matches
+    pub fn set_default(&mut self, callsign: &str) -> QrzLogbookResult<()> {
+        let callsign = normalize(callsign);
+        if !self.clients.contains_key(&callsign) {
+            return Err(QrzLogbookError::NoProfile(callsign));
+        }
+        self.default_callsign = Some(callsign);
+        Ok(())
+    }
+
+    /// Callsigns with a registered client
+    pub fn callsigns(&self) -> Vec<&str> {
+        self.clients.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// Find the client for a station callsign
+    ///
+    /// Exact matches take precedence over base callsign matches, which take
+    /// precedence over the default profile.
+    pub fn client_for(&self, station_callsign: &str) -> QrzLogbookResult<&QrzLogbookClient> {
+        let clients = self
+            .clients
+            .iter()
+            .map(|(callsign, client)| (callsign.as_str(), client));
+        find_by_callsign(clients, station_callsign)
+            .or_else(|| {
+                self.default_callsign
+                    .as_ref()
+                    .and_then(|default| self.clients.get(default))
+            })
+            .ok_or_else(|| QrzLogbookError::NoProfile(normalize(station_callsign)))
+    }
+
+    /// Insert a QSO into the logbook matching its station callsign
+    pub async fn insert_qso(
+        &self,
+        qso: &QsoRecord,
+        replace: bool,
+    ) -> QrzLogbookResult<InsertResponse> {
+        self.client_for(&qso.station_callsign)?
+            .insert_qso(qso, replace)
+            .await
+    }
+
+    /// Insert a batch of QSOs, routing each one to its logbook
+    ///
+    /// Every record is attempted; the results are returned in input order.
+    pub async fn insert_qsos(
+        &self,
+        qsos: &[QsoRecord],
+        replace: bool,
+    ) -> Vec<QrzLogbookResult<InsertResponse>> {
+        let mut results = Vec::with_capacity(qsos.len());
+        for qso in qsos {
+            results.push(self.insert_qso(qso, replace).await);
+        }
+        results
+    }
+}
+
+fn normalize(callsign: &str) -> String {
+    callsign.trim().to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+
+    fn config() -> ProfileConfig {
+        ProfileConfig::from_toml_str(
+            r#"
+default_user_agent = "RouterTest/1.0.0 (W1AW)"
+
+[[profiles]]
+callsign = "W1AW"
+api_key = "1111-AAAA-2222-BBBB"
+
+[[profiles]]
+callsign = "K1ABC"
+api_key = "3333-CCCC-4444-DDDD"
+"#,
+        )
+        .unwrap()
+    }
+
+    /// Router with a W1AW and a K1ABC logbook, each behind its own transport
+    fn memory_router() -> (LogbookRouter, MemoryTransport, MemoryTransport) {
+        let logbook = |callsign: &str, key: &str| {
+            let transport = MemoryTransport::new(|_| Ok("RESULT=OK&LOGID=1&COUNT=1".to_string()));
+            let client = QrzLogbookClient::with_transport(
+                key,
+                format!("RouterTest/1.0.0 ({})", callsign),
+                transport.clone(),
+            )
+            .unwrap();
+            (client, transport)
+        };
+        let (w1aw, w1aw_transport) = logbook("W1AW", "1111-AAAA-2222-BBBB");
+        let (k1abc, k1abc_transport) = logbook("K1ABC", "3333-CCCC-4444-DDDD");
+
+        let mut router = LogbookRouter::new();
+        router.add_client("W1AW", w1aw);
+        router.add_client("K1ABC", k1abc);
+        (router, w1aw_transport, k1abc_transport)
+    }
+
+    async fn insert_from(router: &LogbookRouter, station_callsign: &str) -> QrzLogbookResult<()> {
+        let qso = QsoRecord::builder()
+            .call("JA1XY")
+            .station_callsign(station_callsign)
+            .build();
+        router.insert_qso(&qso, false).await.map(|_| ())
+    }
+
+    #[test]
+    fn test_router_from_config() {
+        let router = LogbookRouter::from_config(&config()).unwrap();
+        assert_eq!(router.callsigns(), vec!["K1ABC", "W1AW"]);
+        assert!(matches!(
+            router.client_for("N0CALL"),
+            Err(QrzLogbookError::NoProfile(call)) if call == "N0CALL"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_router_routes_by_station_callsign() {
+        let (router, w1aw, k1abc) = memory_router();
+
+        insert_from(&router, "w1aw").await.unwrap();
+        insert_from(&router, "K1ABC/P").await.unwrap();
+        insert_from(&router, "VE3/K1ABC").await.unwrap();
+
+        let w1aw_requests = w1aw.requests();
+        assert_eq!(w1aw_requests.len(), 1);
+        assert_eq!(w1aw_requests[0].param("KEY"), Some("1111-AAAA-2222-BBBB"));
+        let k1abc_requests = k1abc.requests();
+        assert_eq!(k1abc_requests.len(), 2);
+        assert!(k1abc_requests
+            .iter()
+            .all(|r| r.param("KEY") == Some("3333-CCCC-4444-DDDD")));
+    }
+
+    #[tokio::test]
+    async fn test_router_default_profile() {
+        let (mut router, w1aw, k1abc) = memory_router();
+        assert!(matches!(
+            insert_from(&router, "N0CALL").await,
+            Err(QrzLogbookError::NoProfile(_))
+        ));
+        assert!(w1aw.requests().is_empty());
+
+        assert!(router.set_default("N0CALL").is_err());
+        router.set_default("k1abc").unwrap();
+        insert_from(&router, "N0CALL").await.unwrap();
+        insert_from(&router, "W1AW/P").await.unwrap();
+        assert_eq!(k1abc.requests().len(), 1);
+        assert_eq!(w1aw.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_insert_without_profile() {
+        let router = LogbookRouter::from_config(&config()).unwrap();
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("N0CALL")
+            .build();
+        let result = router.insert_qso(&qso, false).await;
+        assert!(matches!(result, Err(QrzLogbookError::NoProfile(_))));
+    }
+}
@@ -0,0 +1,325 @@
+//! API key secret handling
+//!
+//! [`ApiKey`] holds a QRZ API key. Its `Debug` output is redacted so keys
+//! don't end up in logs, and its memory is zeroed when it is dropped, as is
+//! the `KEY` parameter of each [`TransportRequest`](crate::transport::TransportRequest).
+//! Copies made outside the crate, such as the form body the HTTP client
+//! encodes, are not zeroed. Keys can be loaded from an environment variable, a
+//! private file or the output of a command such as `pass`.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::secret::ApiKey;
+//! use qrz_logbook_api::QrzLogbookClient;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let api_key = ApiKey::from_env("QRZ_API_KEY")
+//!     .or_else(|_| ApiKey::from_command("pass", ["show", "qrz/api-key"]))?;
+//!
+//! let client = QrzLogbookClient::new(api_key, "MyApp/1.0.0 (YOURCALL)")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{QrzLogbookError, QrzLogbookResult};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{ffi::OsStr, path::Path, process::Command};
+use zeroize::Zeroize;
+
+/// A QRZ API key that is redacted in `Debug` and zeroed on drop
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    /// Read the key from an environment variable
+    pub fn from_env(var: &str) -> QrzLogbookResult<Self> {
+        Self::from_env_value(var, std::env::var(var))
+    }
+
+    fn from_env_value(
+        var: &str,
+        value: Result<String, std::env::VarError>,
+    ) -> QrzLogbookResult<Self> {
+        // NotUnicode carries the raw value, so don't include its Display
+        let value = value.map_err(|e| {
+            let reason = match e {
+                std::env::VarError::NotPresent => "not set",
+                std::env::VarError::NotUnicode(_) => "not valid Unicode",
+            };
+            QrzLogbookError::config(format!("Unable to read API key from ${}: {}", var, reason))
+        })?;
+        Self::from_secret_text(value, &format!("${}", var))
+    }
+
+    /// Read the key from a file
+    ///
+    /// Surrounding whitespace is trimmed. On Unix the file must not be
+    /// readable or writable by group or others (e.g. mode `0600`).
+    pub fn from_file(path: impl AsRef<Path>) -> QrzLogbookResult<Self> {
+        let path = path.as_ref();
+        check_permissions(path)?;
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            QrzLogbookError::config(format!(
+                "Unable to read API key from {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_secret_text(contents, &path.display().to_string())
+    }
+
+    /// Run a command and use the first line of its output as the key
+    ///
+    /// The command is run directly, not through a shell, e.g.
+    /// `ApiKey::from_command("pass", ["show", "qrz/api-key"])`.
+    pub fn from_command<I, S>(program: &str, args: I) -> QrzLogbookResult<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = Command::new(program).args(args).output().map_err(|e| {
+            QrzLogbookError::config(format!("Unable to run API key command {}: {}", program, e))
+        })?;
+
+        if !output.status.success() {
+            return Err(QrzLogbookError::config(format!(
+                "API key command {} failed ({}): {}",
+                program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let mut stdout = String::from_utf8(output.stdout).map_err(|_| {
+            QrzLogbookError::config(format!("API key command {} printed invalid UTF-8", program))
+        })?;
+        let first_line = stdout.lines().next().unwrap_or_default().to_string();
+        stdout.zeroize();
+        Self::from_secret_text(first_line, &format!("command {}", program))
+    }
+
+    /// Check the key format and return it normalized
+    ///
+    /// QRZ logbook keys are four groups of four letters or digits,
+    /// `XXXX-XXXX-XXXX-XXXX`. Whitespace is removed, letters are uppercased
+    /// and the dashes are added if missing.
+    pub fn normalize(&self) -> QrzLogbookResult<ApiKey> {
+        let compact = ApiKey(
+            self.0
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| c.to_ascii_uppercase())
+                .collect(),
+        );
+
+        let valid_group =
+            |group: &str| group.len() == 4 && group.chars().all(|c| c.is_ascii_alphanumeric());
+        let groups: Vec<&str> = if compact.0.contains('-') {
+            compact.0.split('-').collect()
+        } else if compact.0.len() == 16 && compact.0.is_ascii() {
+            (0..4).map(|i| &compact.0[i * 4..i * 4 + 4]).collect()
+        } else {
+            Vec::new()
+        };
+
+        if groups.len() != 4 || !groups.iter().all(|g| valid_group(g)) {
+            return Err(QrzLogbookError::InvalidKey);
+        }
+        Ok(ApiKey(groups.join("-")))
+    }
+
+    /// The key itself, for sending to the API
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    fn from_secret_text(mut text: String, source: &str) -> QrzLogbookResult<Self> {
+        let key = Self::new(text.trim());
+        text.zeroize();
+        if key.0.is_empty() {
+            return Err(QrzLogbookError::config(format!(
+                "API key from {} is empty",
+                source
+            )));
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> QrzLogbookResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = std::fs::metadata(path).map_err(|e| {
+        QrzLogbookError::config(format!(
+            "Unable to read API key from {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let mode = metadata.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(QrzLogbookError::config(format!(
+            "API key file {} is accessible by other users (mode {:o}); run chmod 600 on it",
+            path.display(),
+            mode
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> QrzLogbookResult<()> {
+    Ok(())
+}
+
+impl Drop for ApiKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ApiKey([REDACTED])")
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<&String> for ApiKey {
+    fn from(key: &String) -> Self {
+        Self::new(key.as_str())
+    }
+}
+
+impl Serialize for ApiKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(ApiKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let key = ApiKey::new("ABCD-1234-EF56-7890");
+        assert_eq!(format!("{:?}", key), "ApiKey([REDACTED])");
+        assert_eq!(key.expose(), "ABCD-1234-EF56-7890");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let normalize = |key: &str| ApiKey::new(key).normalize().map(|k| k.expose().to_string());
+
+        assert_eq!(
+            normalize("ABCD-1234-EF56-7890").unwrap(),
+            "ABCD-1234-EF56-7890"
+        );
+        assert_eq!(
+            normalize(" abcd-1234-ef56-7890\n").unwrap(),
+            "ABCD-1234-EF56-7890"
+        );
+        assert_eq!(
+            normalize("abcd1234ef567890").unwrap(),
+            "ABCD-1234-EF56-7890"
+        );
+        assert_eq!(
+            normalize("ABCD 1234 EF56 7890").unwrap(),
+            "ABCD-1234-EF56-7890"
+        );
+
+        for invalid in [
+            "",
+            "short",
+            "test-api-key-12345",
+            "ABCD-1234-EF56",
+            "ABCD-1234-EF56-789!",
+            "ABCDE-234-EF56-7890",
+        ] {
+            assert!(
+                matches!(normalize(invalid), Err(QrzLogbookError::InvalidKey)),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_env() {
+        // Setting variables would race with other tests reading the environment
+        let key = ApiKey::from_env_value("QRZ_API_KEY", Ok(" ABCD-1234-EF56-7890\n".to_string()))
+            .unwrap();
+        assert_eq!(key.expose(), "ABCD-1234-EF56-7890");
+
+        let err = ApiKey::from_env("QRZ_LOGBOOK_API_TEST_MISSING").unwrap_err();
+        assert!(err.to_string().contains("$QRZ_LOGBOOK_API_TEST_MISSING"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_env_not_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let raw = std::ffi::OsString::from_vec(b"ABCD-1234-EF56-789\xff".to_vec());
+        let err = ApiKey::from_env_value("QRZ_API_KEY", Err(std::env::VarError::NotUnicode(raw)))
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("not valid Unicode"));
+        assert!(!message.contains("ABCD-1234"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_file_checks_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "ABCD-1234-EF56-7890").unwrap();
+
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o644)).unwrap();
+        let err = ApiKey::from_file(file.path()).unwrap_err();
+        assert!(err.to_string().contains("chmod 600"));
+
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o600)).unwrap();
+        let key = ApiKey::from_file(file.path()).unwrap();
+        assert_eq!(key.expose(), "ABCD-1234-EF56-7890");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_command() {
+        let key = ApiKey::from_command("echo", ["ABCD-1234-EF56-7890"]).unwrap();
+        assert_eq!(key.expose(), "ABCD-1234-EF56-7890");
+
+        assert!(ApiKey::from_command("false", [] as [&str; 0]).is_err());
+        assert!(ApiKey::from_command("true", [] as [&str; 0])
+            .unwrap_err()
+            .to_string()
+            .contains("empty"));
+        assert!(ApiKey::from_command("/nonexistent/qrz-key-helper", ["x"]).is_err());
+    }
+}
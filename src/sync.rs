@@ -0,0 +1,139 @@
+//! Logbook synchronisation helpers
+//!
+//! [`reconcile`] compares a locally known set of logids with the logids on
+//! the server, as returned by
+//! [`QrzLogbookClient::fetch_logids`](crate::QrzLogbookClient::fetch_logids),
+//! to find records added or deleted on QRZ without downloading any ADIF.
+//!
+//! [`HighWaterMark`] persists the time of the last download so that
+//! [`QrzLogbookClient::fetch_incremental`](crate::QrzLogbookClient::fetch_incremental)
+//! only asks for records modified since then.
+
+use crate::error::QrzLogbookResult;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+/// Difference between local and server logids
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogidDiff {
+    /// On the server but not known locally, in ascending order
+    pub added: Vec<u64>,
+    /// Known locally but no longer on the server, in ascending order
+    pub removed: Vec<u64>,
+}
+
+impl LogidDiff {
+    /// True when the local set matches the server
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare local logids with the server's logids
+pub fn reconcile(
+    local: impl IntoIterator<Item = u64>,
+    remote: impl IntoIterator<Item = u64>,
+) -> LogidDiff {
+    let local: BTreeSet<u64> = local.into_iter().collect();
+    let remote: BTreeSet<u64> = remote.into_iter().collect();
+
+    LogidDiff {
+        added: remote.difference(&local).copied().collect(),
+        removed: local.difference(&remote).copied().collect(),
+    }
+}
+
+/// Persistent timestamp of the last incremental download
+#[derive(Debug, Clone, Default)]
+pub struct HighWaterMark {
+    path: Option<PathBuf>,
+    since: Option<DateTime<Utc>>,
+}
+
+impl HighWaterMark {
+    /// Create an in-memory mark that is never persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the mark from a JSON file, starting unset if the file doesn't exist
+    ///
+    /// The mark is written back to the same file whenever it is updated.
+    pub fn load(path: impl AsRef<Path>) -> QrzLogbookResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let since = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: Some(path),
+            since,
+        })
+    }
+
+    /// Time of the last download, if any
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.since
+    }
+
+    /// Record the time of a completed download
+    pub fn set(&mut self, since: DateTime<Utc>) -> QrzLogbookResult<()> {
+        self.since = Some(since);
+        self.save()
+    }
+
+    /// Forget the mark, so the next download fetches everything
+    pub fn reset(&mut self) -> QrzLogbookResult<()> {
+        self.since = None;
+        self.save()
+    }
+
+    fn save(&self) -> QrzLogbookResult<()> {
+        if let Some(ref path) = self.path {
+            std::fs::write(path, serde_json::to_string(&self.since)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reconcile() {
+        let diff = reconcile([5, 1, 2, 3, 3], [4, 2, 3, 6]);
+        assert_eq!(diff.added, vec![4, 6]);
+        assert_eq!(diff.removed, vec![1, 5]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_in_sync() {
+        assert!(reconcile([1, 2], [2, 1]).is_empty());
+        assert!(reconcile(Vec::new(), Vec::new()).is_empty());
+        assert_eq!(reconcile(Vec::new(), [7]).added, vec![7]);
+    }
+
+    #[test]
+    fn test_high_water_mark_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mark.json");
+
+        let mut mark = HighWaterMark::load(&path).unwrap();
+        assert_eq!(mark.since(), None);
+
+        let since = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        mark.set(since).unwrap();
+        assert_eq!(HighWaterMark::load(&path).unwrap().since(), Some(since));
+
+        mark.reset().unwrap();
+        assert_eq!(HighWaterMark::load(&path).unwrap().since(), None);
+    }
+}
@@ -0,0 +1,719 @@
+//! Fake QRZ Logbook API for integration tests
+//!
+//! Enabled with the `testing` feature. [`FakeLogbook`] is a stateful
+//! in-memory logbook that answers API requests the way QRZ does:
+//!
+//! - INSERT detects duplicates (same call, date, time on, band and mode) and
+//!   replaces them with the REPLACE option
+//! - DELETE reports PARTIAL results for unknown logids
+//! - FETCH supports every [`FetchOptions`](crate::FetchOptions) filter,
+//!   `MAX`/`AFTERLOGID` paging and `TYPE:LOGIDS`
+//! - STATUS reports record counts, the owner callsign and book ID
+//! - unknown API keys get `RESULT=FAIL&REASON=invalid api key` and keys
+//!   without logbook privileges get `RESULT=AUTH`
+//!
+//! Failures and latency can be injected. A `FakeLogbook` can be used directly
+//! as a [`Transport`], or served over HTTP on localhost with
+//! [`FakeQrzServer`] to test the full stack.
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::testing::{FakeLogbook, FakeQrzServer};
+//! use qrz_logbook_api::FetchOptions;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let logbook = FakeLogbook::new("K1ABC", "ABCD-1234-EF56-7890");
+//! let server = FakeQrzServer::start(logbook.clone()).await;
+//! let client = server.client("ABCD-1234-EF56-7890", "MyApp/1.0.0 (K1ABC)")?;
+//!
+//! // ... exercise your application against `client` ...
+//! let qsos = client.fetch_all_qsos(&FetchOptions::all()).await?;
+//! assert_eq!(qsos.len(), logbook.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::AdifParser,
+    client::QrzLogbookClient,
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::{QslStatus, QsoRecord},
+    secret::ApiKey,
+    transport::{ReqwestTransport, Transport, TransportFuture, TransportRequest},
+};
+use chrono::{NaiveDate, Utc};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, Request, Respond, ResponseTemplate,
+};
+
+/// Book ID reported by STATUS
+pub const BOOK_ID: u64 = 12345;
+
+/// Failure injected into the next request
+#[derive(Debug, Clone, PartialEq)]
+pub enum FakeFailure {
+    /// Answer with this response body instead of handling the request
+    Response(String),
+    /// Answer with this HTTP status code
+    ///
+    /// Only [`FakeQrzServer`] can send a status; the in-memory transport
+    /// returns an API error naming the status instead.
+    HttpStatus(u16),
+}
+
+/// A record stored in a [`FakeLogbook`]
+#[derive(Debug, Clone)]
+pub struct FakeRecord {
+    pub logid: u64,
+    pub qso: QsoRecord,
+    /// Date of the last insert or replace, used by `MODSINCE`
+    pub modified: NaiveDate,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    records: BTreeMap<u64, FakeRecord>,
+    next_logid: u64,
+    failures: VecDeque<FakeFailure>,
+    latency: Option<Duration>,
+    requests: Vec<TransportRequest>,
+    unprivileged_keys: HashSet<String>,
+}
+
+/// Stateful in-memory QRZ logbook
+///
+/// Clones share the same state, so a test can keep a handle to inspect the
+/// logbook while the client under test talks to it.
+#[derive(Debug, Clone)]
+pub struct FakeLogbook {
+    owner: String,
+    keys: Arc<HashSet<String>>,
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeLogbook {
+    /// Create an empty logbook owned by `owner` that accepts `api_key`
+    pub fn new(owner: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self::with_keys(owner, [api_key.into()])
+    }
+
+    /// Create an empty logbook that accepts any of the API keys
+    pub fn with_keys<I, S>(owner: impl Into<String>, api_keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            owner: owner.into().to_uppercase(),
+            keys: Arc::new(
+                api_keys
+                    .into_iter()
+                    .map(|k| k.into().to_uppercase())
+                    .collect(),
+            ),
+            state: Arc::new(Mutex::new(State {
+                next_logid: 1000,
+                ..Default::default()
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Add a record directly, bypassing duplicate checks
+    pub fn seed(&self, qso: QsoRecord) -> u64 {
+        let mut state = self.state();
+        let logid = state.next_logid;
+        state.next_logid += 1;
+        state.records.insert(
+            logid,
+            FakeRecord {
+                logid,
+                qso,
+                modified: Utc::now().date_naive(),
+            },
+        );
+        logid
+    }
+
+    /// Stored record by logid
+    pub fn get(&self, logid: u64) -> Option<FakeRecord> {
+        self.state().records.get(&logid).cloned()
+    }
+
+    /// All stored records in logid order
+    pub fn records(&self) -> Vec<FakeRecord> {
+        self.state().records.values().cloned().collect()
+    }
+
+    /// Number of stored records
+    pub fn len(&self) -> usize {
+        self.state().records.len()
+    }
+
+    /// Whether the logbook is empty
+    pub fn is_empty(&self) -> bool {
+        self.state().records.is_empty()
+    }
+
+    /// Mark a record as confirmed via QRZ, LoTW or paper QSL
+    pub fn confirm(&self, logid: u64) -> bool {
+        match self.state().records.get_mut(&logid) {
+            Some(record) => {
+                record.qso.qsl_rcvd = Some(QslStatus::Yes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the modification date of a record, for `MODSINCE` tests
+    pub fn set_modified(&self, logid: u64, date: NaiveDate) -> bool {
+        match self.state().records.get_mut(&logid) {
+            Some(record) => {
+                record.modified = date;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Accept a key that QRZ knows but that lacks logbook privileges
+    ///
+    /// Every request made with it gets `RESULT=AUTH`.
+    pub fn add_unprivileged_key(&self, api_key: impl Into<String>) {
+        self.state()
+            .unprivileged_keys
+            .insert(api_key.into().to_uppercase());
+    }
+
+    /// Fail the next request instead of handling it
+    ///
+    /// Failures queue up and are used one per request.
+    pub fn fail_next(&self, failure: FakeFailure) {
+        self.state().failures.push_back(failure);
+    }
+
+    /// Delay every response
+    pub fn set_latency(&self, latency: Option<Duration>) {
+        self.state().latency = latency;
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Handle a request, returning the injected failure if there is one
+    fn dispatch(&self, request: TransportRequest) -> (Result<String, u16>, Option<Duration>) {
+        let mut state = self.state();
+        let latency = state.latency;
+        let failure = state.failures.pop_front();
+        state.requests.push(request.clone());
+        drop(state);
+
+        let response = match failure {
+            Some(FakeFailure::Response(body)) => Ok(body),
+            Some(FakeFailure::HttpStatus(status)) => Err(status),
+            None => Ok(self.handle(&request)),
+        };
+        (response, latency)
+    }
+
+    /// Answer an API request with a response body
+    pub fn handle(&self, request: &TransportRequest) -> String {
+        let key = request.param("KEY").unwrap_or_default();
+        if self.state().unprivileged_keys.contains(key) {
+            return "RESULT=AUTH".to_string();
+        }
+        if !self.keys.contains(key) {
+            return fail("invalid api key");
+        }
+
+        match request.param("ACTION").map(|a| a.to_uppercase()).as_deref() {
+            Some("INSERT") => self.insert(request),
+            Some("DELETE") => self.delete(request),
+            Some("STATUS") => self.status(),
+            Some("FETCH") => self.fetch(request),
+            _ => fail("Invalid ACTION"),
+        }
+    }
+
+    fn insert(&self, request: &TransportRequest) -> String {
+        let qso = match AdifParser::parse_adif(request.param("ADIF").unwrap_or_default()) {
+            Ok(mut qsos) if qsos.len() == 1 => qsos.remove(0),
+            Ok(_) => return fail("ADIF must contain exactly one record"),
+            Err(e) => return fail(&e.to_string()),
+        };
+        let replace = request
+            .param("OPTION")
+            .is_some_and(|o| o.eq_ignore_ascii_case("REPLACE"));
+
+        let mut state = self.state();
+        let duplicate = state
+            .records
+            .values()
+            .find(|record| is_duplicate(&record.qso, &qso))
+            .map(|record| record.logid);
+
+        let (logid, result) = match (duplicate, replace) {
+            (Some(_), false) => {
+                return fail("Unable to add QSO to database: duplicate");
+            }
+            (Some(logid), true) => (logid, "REPLACE"),
+            (None, _) => {
+                let logid = state.next_logid;
+                state.next_logid += 1;
+                (logid, "OK")
+            }
+        };
+
+        state.records.insert(
+            logid,
+            FakeRecord {
+                logid,
+                qso,
+                modified: Utc::now().date_naive(),
+            },
+        );
+        format!("RESULT={}&LOGID={}&COUNT=1", result, logid)
+    }
+
+    fn delete(&self, request: &TransportRequest) -> String {
+        let logids: Vec<u64> = request
+            .param("LOGIDS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect();
+        if logids.is_empty() {
+            return fail("No LOGIDS given");
+        }
+
+        let mut state = self.state();
+        let mut missing = Vec::new();
+        for logid in &logids {
+            if state.records.remove(logid).is_none() {
+                missing.push(logid.to_string());
+            }
+        }
+
+        let deleted = logids.len() - missing.len();
+        match (deleted, missing.is_empty()) {
+            (_, true) => format!("RESULT=OK&COUNT={}", deleted),
+            (0, false) => format!(
+                "RESULT=FAIL&REASON=LOGIDS not found&COUNT=0&LOGIDS={}",
+                encode(&missing.join(","))
+            ),
+            (_, false) => format!(
+                "RESULT=PARTIAL&COUNT={}&LOGIDS={}",
+                deleted,
+                encode(&missing.join(","))
+            ),
+        }
+    }
+
+    fn status(&self) -> String {
+        let state = self.state();
+        let records = state.records.values();
+        let confirmed = records.clone().filter(|r| is_confirmed(&r.qso)).count();
+        let dxcc_total = records
+            .clone()
+            .filter_map(|r| r.qso.dxcc)
+            .collect::<HashSet<_>>()
+            .len();
+        let start_date = records.clone().map(|r| r.qso.qso_date).min();
+        let end_date = records.map(|r| r.qso.qso_date).max();
+
+        let mut data = vec![
+            format!("callsign={}", self.owner),
+            format!("owner={}", self.owner),
+            format!("bookid={}", BOOK_ID),
+            format!("total_qsos={}", state.records.len()),
+            format!("confirmed={}", confirmed),
+            format!("dxcc_total={}", dxcc_total),
+        ];
+        if let (Some(start), Some(end)) = (start_date, end_date) {
+            data.push(format!("start_date={}", start.format("%Y-%m-%d")));
+            data.push(format!("end_date={}", end.format("%Y-%m-%d")));
+        }
+
+        format!("RESULT=OK&DATA={}", encode(&data.join("&")))
+    }
+
+    fn fetch(&self, request: &TransportRequest) -> String {
+        let options = match FetchFilter::parse(request.param("OPTION").unwrap_or_default()) {
+            Ok(options) => options,
+            Err(reason) => return fail(&reason),
+        };
+
+        let state = self.state();
+        let matches: Vec<&FakeRecord> = state
+            .records
+            .values()
+            .filter(|record| options.matches(record))
+            .take(options.max.unwrap_or(usize::MAX))
+            .collect();
+
+        let logids: Vec<String> = matches.iter().map(|r| r.logid.to_string()).collect();
+        let mut response = format!(
+            "RESULT=OK&COUNT={}&LOGIDS={}",
+            matches.len(),
+            encode(&logids.join(","))
+        );
+
+        if !options.logids_only && !matches.is_empty() {
+            let adif: String = matches
+                .iter()
+                .map(|record| {
+                    let logid = record.logid.to_string();
+                    let mut adif = AdifParser::to_adif(&record.qso);
+                    adif.insert_str(
+                        adif.len() - "<eor>".len(),
+                        &format!("<app_qrzlog_logid:{}>{}", logid.len(), logid),
+                    );
+                    adif
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            response.push_str(&format!("&ADIF={}", encode(&adif)));
+        }
+
+        response
+    }
+}
+
+impl Transport for FakeLogbook {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        let (response, latency) = self.dispatch(request);
+        Box::pin(async move {
+            if let Some(latency) = latency {
+                tokio::time::sleep(latency).await;
+            }
+            response.map_err(|status| QrzLogbookError::api_error(format!("HTTP status {}", status)))
+        })
+    }
+}
+
+/// [`FakeLogbook`] served over HTTP on localhost
+pub struct FakeQrzServer {
+    server: MockServer,
+    logbook: FakeLogbook,
+}
+
+impl FakeQrzServer {
+    /// Start a server on a random localhost port
+    pub async fn start(logbook: FakeLogbook) -> Self {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api"))
+            .respond_with(Responder(logbook.clone()))
+            .mount(&server)
+            .await;
+
+        Self { server, logbook }
+    }
+
+    /// API endpoint URL, e.g. `http://127.0.0.1:41234/api`
+    pub fn endpoint(&self) -> String {
+        format!("{}/api", self.server.uri())
+    }
+
+    /// The logbook behind the server
+    pub fn logbook(&self) -> &FakeLogbook {
+        &self.logbook
+    }
+
+    /// Create a client that talks to this server over HTTP
+    pub fn client(
+        &self,
+        api_key: impl Into<ApiKey>,
+        user_agent: impl Into<String>,
+    ) -> QrzLogbookResult<QrzLogbookClient> {
+        let user_agent = user_agent.into();
+        let transport = ReqwestTransport::new(&user_agent)?.endpoint(self.endpoint());
+        QrzLogbookClient::with_transport(api_key, user_agent, transport)
+    }
+}
+
+struct Responder(FakeLogbook);
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let params = url::form_urlencoded::parse(&request.body)
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        let mut received = TransportRequest::new(params);
+        for (name, value) in request.headers.iter() {
+            received.add_header(name.as_str(), value.to_str().unwrap_or_default());
+        }
+
+        let (response, latency) = self.0.dispatch(received);
+        let template = match response {
+            Ok(body) => ResponseTemplate::new(200).set_body_string(body),
+            Err(status) => ResponseTemplate::new(status),
+        };
+        match latency {
+            Some(latency) => template.set_delay(latency),
+            None => template,
+        }
+    }
+}
+
+/// Parsed FETCH options
+#[derive(Debug, Default)]
+struct FetchFilter {
+    band: Option<String>,
+    mode: Option<String>,
+    call: Option<String>,
+    max: Option<usize>,
+    after_logid: Option<u64>,
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+    confirmed: Option<bool>,
+    modsince: Option<NaiveDate>,
+    dxcc: Option<u32>,
+    logids: Option<HashSet<u64>>,
+    logids_only: bool,
+}
+
+impl FetchFilter {
+    fn parse(option: &str) -> Result<Self, String> {
+        let mut filter = FetchFilter::default();
+        let mut tokens = option.split(',').map(str::trim).filter(|t| !t.is_empty());
+
+        while let Some(token) = tokens.next() {
+            let (name, value) = token.split_once(':').unwrap_or((token, ""));
+            let invalid = || format!("Invalid option {}", token);
+            let date = |format| NaiveDate::parse_from_str(value, format).map_err(|_| invalid());
+
+            match name.to_uppercase().as_str() {
+                "ALL" => {}
+                "BAND" => filter.band = Some(value.to_string()),
+                "MODE" => filter.mode = Some(value.to_string()),
+                "CALL" => filter.call = Some(value.to_string()),
+                "MAX" => filter.max = Some(value.parse().map_err(|_| invalid())?),
+                "AFTERLOGID" => filter.after_logid = Some(value.parse().map_err(|_| invalid())?),
+                "DATEFROM" => filter.date_from = Some(date("%Y%m%d")?),
+                "DATETO" => filter.date_to = Some(date("%Y%m%d")?),
+                "MODSINCE" => filter.modsince = Some(date("%Y-%m-%d")?),
+                "DXCC" => filter.dxcc = Some(value.parse().map_err(|_| invalid())?),
+                "STATUS" => {
+                    filter.confirmed = match value.to_uppercase().as_str() {
+                        "CONFIRMED" => Some(true),
+                        "UNCONFIRMED" => Some(false),
+                        _ => return Err(invalid()),
+                    }
+                }
+                "TYPE" => {
+                    filter.logids_only = match value.to_uppercase().as_str() {
+                        "LOGIDS" => true,
+                        "ADIF" => false,
+                        _ => return Err(invalid()),
+                    }
+                }
+                // The logid list is comma separated and runs to the end
+                "LOGIDS" => {
+                    let logids = std::iter::once(value)
+                        .chain(tokens.by_ref())
+                        .map(|id| id.parse().map_err(|_| invalid()))
+                        .collect::<Result<_, _>>()?;
+                    filter.logids = Some(logids);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(filter)
+    }
+
+    fn matches(&self, record: &FakeRecord) -> bool {
+        let qso = &record.qso;
+        let same = |wanted: &Option<String>, actual: &str| {
+            wanted
+                .as_ref()
+                .is_none_or(|w| w.eq_ignore_ascii_case(actual))
+        };
+
+        same(&self.band, &qso.band)
+            && same(&self.mode, &qso.mode)
+            && same(&self.call, &qso.call)
+            && self.after_logid.is_none_or(|id| record.logid >= id)
+            && self.date_from.is_none_or(|d| qso.qso_date >= d)
+            && self.date_to.is_none_or(|d| qso.qso_date <= d)
+            && self.modsince.is_none_or(|d| record.modified >= d)
+            && self.dxcc.is_none_or(|d| qso.dxcc == Some(d))
+            && self.confirmed.is_none_or(|c| is_confirmed(qso) == c)
+            && self
+                .logids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&record.logid))
+    }
+}
+
+fn is_duplicate(a: &QsoRecord, b: &QsoRecord) -> bool {
+    a.call.eq_ignore_ascii_case(&b.call)
+        && a.qso_date == b.qso_date
+        && a.time_on.format("%H%M").to_string() == b.time_on.format("%H%M").to_string()
+        && a.band.eq_ignore_ascii_case(&b.band)
+        && a.mode.eq_ignore_ascii_case(&b.mode)
+}
+
+fn is_confirmed(qso: &QsoRecord) -> bool {
+    [qso.qsl_rcvd, qso.lotw_qsl_rcvd]
+        .iter()
+        .any(|status| matches!(status, Some(QslStatus::Yes) | Some(QslStatus::Verified)))
+}
+
+fn fail(reason: &str) -> String {
+    format!("RESULT=FAIL&REASON={}", encode(reason))
+}
+
+fn encode(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    const KEY: &str = "ABCD-1234-EF56-7890";
+
+    fn qso(call: &str, band: &str, day: u32) -> QsoRecord {
+        QsoRecord::builder()
+            .call(call)
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, day).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band(band)
+            .mode("SSB")
+            .build()
+    }
+
+    fn request(params: &[(&str, &str)]) -> TransportRequest {
+        TransportRequest::new(
+            std::iter::once(("KEY", KEY))
+                .chain(params.iter().copied())
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_insert_duplicate_and_replace() {
+        let logbook = FakeLogbook::new("k1abc", KEY);
+        let adif = AdifParser::to_adif(&qso("W1AW", "20m", 15));
+
+        let insert = request(&[("ACTION", "INSERT"), ("ADIF", &adif)]);
+        assert_eq!(logbook.handle(&insert), "RESULT=OK&LOGID=1000&COUNT=1");
+        assert!(logbook.handle(&insert).starts_with("RESULT=FAIL&REASON="));
+
+        let replace = request(&[("ACTION", "INSERT"), ("ADIF", &adif), ("OPTION", "REPLACE")]);
+        assert_eq!(
+            logbook.handle(&replace),
+            "RESULT=REPLACE&LOGID=1000&COUNT=1"
+        );
+        assert_eq!(logbook.len(), 1);
+    }
+
+    #[test]
+    fn test_delete_partial() {
+        let logbook = FakeLogbook::new("K1ABC", KEY);
+        let first = logbook.seed(qso("W1AW", "20m", 15));
+        logbook.seed(qso("DL1AB", "40m", 16));
+
+        let delete = request(&[("ACTION", "DELETE"), ("LOGIDS", &format!("{},9", first))]);
+        assert_eq!(logbook.handle(&delete), "RESULT=PARTIAL&COUNT=1&LOGIDS=9");
+        assert!(logbook.handle(&delete).starts_with("RESULT=FAIL"));
+        assert_eq!(logbook.len(), 1);
+    }
+
+    #[test]
+    fn test_fetch_filters_and_paging() {
+        let logbook = FakeLogbook::new("K1ABC", KEY);
+        let ids: Vec<u64> = (1..=5)
+            .map(|day| logbook.seed(qso("W1AW", if day % 2 == 0 { "40m" } else { "20m" }, day)))
+            .collect();
+        logbook.confirm(ids[4]);
+
+        let fetch = |option: &str| {
+            let filter = FetchFilter::parse(option).unwrap();
+            logbook
+                .records()
+                .into_iter()
+                .filter(|r| filter.matches(r))
+                .map(|r| r.logid)
+                .take(filter.max.unwrap_or(usize::MAX))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fetch("BAND:40m"), vec![ids[1], ids[3]]);
+        assert_eq!(fetch("DATEFROM:20240102,DATETO:20240103"), ids[1..3]);
+        assert_eq!(fetch(&format!("AFTERLOGID:{},MAX:2", ids[2])), ids[2..4]);
+        assert_eq!(fetch("STATUS:CONFIRMED"), vec![ids[4]]);
+        assert_eq!(
+            fetch(&format!("LOGIDS:{},{}", ids[0], ids[3])),
+            vec![ids[0], ids[3]]
+        );
+        assert!(FetchFilter::parse("BOGUS:1").is_err());
+
+        let response = logbook.handle(&request(&[
+            ("ACTION", "FETCH"),
+            ("OPTION", "TYPE:LOGIDS,BAND:20m"),
+        ]));
+        assert_eq!(response, "RESULT=OK&COUNT=3&LOGIDS=1000%2C1002%2C1004");
+    }
+
+    #[test]
+    fn test_status_and_auth() {
+        let logbook = FakeLogbook::new("K1ABC", KEY);
+        let logid = logbook.seed(qso("W1AW", "20m", 15));
+        logbook.confirm(logid);
+
+        let status = logbook.handle(&request(&[("ACTION", "STATUS")]));
+        assert!(status.contains("total_qsos%3D1"));
+        assert!(status.contains("confirmed%3D1"));
+
+        let with_key = |key: &str| {
+            TransportRequest::new(vec![
+                ("KEY".to_string(), key.to_string()),
+                ("ACTION".to_string(), "STATUS".to_string()),
+            ])
+        };
+        assert_eq!(
+            logbook.handle(&with_key("wrong")),
+            "RESULT=FAIL&REASON=invalid%20api%20key"
+        );
+
+        logbook.add_unprivileged_key("9999-9999-9999-9999");
+        assert_eq!(
+            logbook.handle(&with_key("9999-9999-9999-9999")),
+            "RESULT=AUTH"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_injected_failures() {
+        let logbook = FakeLogbook::new("K1ABC", KEY);
+        logbook.fail_next(FakeFailure::Response("RESULT=FAIL&REASON=busy".to_string()));
+        logbook.fail_next(FakeFailure::HttpStatus(503));
+
+        let status = request(&[("ACTION", "STATUS")]);
+        assert_eq!(
+            logbook.send(status.clone()).await.unwrap(),
+            "RESULT=FAIL&REASON=busy"
+        );
+        assert!(logbook.send(status.clone()).await.is_err());
+        assert!(logbook.send(status).await.unwrap().starts_with("RESULT=OK"));
+        assert_eq!(logbook.requests().len(), 3);
+    }
+}
@@ -0,0 +1,327 @@
+//! Pluggable HTTP transport for the QRZ Logbook API
+//!
+//! [`QrzLogbookClient`](crate::QrzLogbookClient) sends every API call as a
+//! [`TransportRequest`] of form parameters and expects the raw response body
+//! back. [`ReqwestTransport`] is the default; [`MemoryTransport`] answers
+//! requests in-process for application tests, and other HTTP stacks or
+//! runtimes can implement [`Transport`] themselves.
+//!
+//! # Example
+//! ```rust
+//! use qrz_logbook_api::transport::MemoryTransport;
+//! use qrz_logbook_api::QrzLogbookClient;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let transport = MemoryTransport::new(|request| {
+//!     assert_eq!(request.param("ACTION"), Some("STATUS"));
+//!     Ok("RESULT=OK&DATA=total_qsos%3D42".to_string())
+//! });
+//! let client = QrzLogbookClient::with_transport(
+//!     "ABCD-1234-EF56-7890",
+//!     "MyApp/1.0.0 (N0CALL)",
+//!     transport.clone(),
+//! )?;
+//!
+//! let status = client.get_status().await?;
+//! assert_eq!(status.data["total_qsos"], "42");
+//! assert_eq!(transport.requests().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{QrzLogbookError, QrzLogbookResult};
+use reqwest::Client;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use zeroize::Zeroize;
+
+/// Default QRZ Logbook API endpoint
+pub const API_ENDPOINT: &str = "https://logbook.qrz.com/api";
+
+/// Future returned by [`Transport::send`]
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = QrzLogbookResult<String>> + Send + 'a>>;
+
+/// A single API call
+///
+/// The `Debug` output redacts the `KEY` parameter and header values so
+/// requests can be logged, and the `KEY` value is zeroed when the request is
+/// dropped.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct TransportRequest {
+    /// Form parameters (`KEY`, `ACTION`, `ADIF`, `OPTION`, ...)
+    pub params: Vec<(String, String)>,
+    /// Extra HTTP headers
+    pub headers: Vec<(String, String)>,
+}
+
+impl TransportRequest {
+    pub fn new(params: Vec<(String, String)>) -> Self {
+        Self {
+            params,
+            headers: Vec::new(),
+        }
+    }
+
+    /// The `ACTION` parameter
+    pub fn action(&self) -> Option<&str> {
+        self.param("ACTION")
+    }
+
+    /// Value of a form parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set a form parameter, replacing any existing value
+    pub fn set_param(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = value.into();
+        match self.params.iter_mut().find(|(key, _)| *key == name) {
+            Some((key, existing)) => {
+                if key == "KEY" {
+                    existing.zeroize();
+                }
+                *existing = value
+            }
+            None => self.params.push((name, value)),
+        }
+    }
+
+    /// Value of an HTTP header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Add an HTTP header
+    pub fn add_header(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.headers.push((name.into(), value.into()));
+    }
+}
+
+impl Drop for TransportRequest {
+    fn drop(&mut self) {
+        for (key, value) in &mut self.params {
+            if key == "KEY" {
+                value.zeroize();
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for TransportRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(key, value)| match key.as_str() {
+                "KEY" => (key.as_str(), "[REDACTED]"),
+                _ => (key.as_str(), value.as_str()),
+            })
+            .collect();
+        let headers: Vec<_> = self.headers.iter().map(|(key, _)| key).collect();
+        f.debug_struct("TransportRequest")
+            .field("params", &params)
+            .field("headers", &headers)
+            .finish()
+    }
+}
+
+/// Sends API requests and returns the response body
+pub trait Transport: Send + Sync {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_>;
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        (**self).send(request)
+    }
+}
+
+/// Transport that POSTs form data with reqwest
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+    endpoint: String,
+}
+
+impl ReqwestTransport {
+    /// Create a transport for the QRZ endpoint with the given user agent
+    pub fn new(user_agent: &str) -> QrzLogbookResult<Self> {
+        let client = Client::builder().user_agent(user_agent).build()?;
+        Ok(Self::from_client(client))
+    }
+
+    /// Use a preconfigured reqwest client (proxies, timeouts, TLS)
+    pub fn from_client(client: Client) -> Self {
+        Self {
+            client,
+            endpoint: API_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Send requests to another endpoint, e.g. a local test server
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut builder = self.client.post(&self.endpoint).form(&request.params);
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            let response = builder.send().await?;
+
+            if !response.status().is_success() {
+                return Err(QrzLogbookError::Http(
+                    response.error_for_status().unwrap_err(),
+                ));
+            }
+
+            Ok(response.text().await?)
+        })
+    }
+}
+
+type Handler = dyn Fn(&TransportRequest) -> QrzLogbookResult<String> + Send + Sync;
+
+/// In-memory transport that answers requests with a handler function
+///
+/// Every request is recorded and can be inspected with
+/// [`requests`](Self::requests). Clones share the handler and the record.
+#[derive(Clone)]
+pub struct MemoryTransport {
+    handler: Arc<Handler>,
+    requests: Arc<Mutex<Vec<TransportRequest>>>,
+}
+
+impl MemoryTransport {
+    /// Answer every request with the handler
+    pub fn new(
+        handler: impl Fn(&TransportRequest) -> QrzLogbookResult<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Answer requests with canned response bodies, in order
+    ///
+    /// Requests after the last response fail with an API error.
+    pub fn with_responses<I, S>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let responses: Mutex<VecDeque<String>> =
+            Mutex::new(responses.into_iter().map(Into::into).collect());
+        Self::new(move |_| {
+            responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| QrzLogbookError::api_error("No more canned responses"))
+        })
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl std::fmt::Debug for MemoryTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryTransport")
+            .field("requests", &self.requests.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, request: TransportRequest) -> TransportFuture<'_> {
+        let response = (self.handler)(&request);
+        self.requests.lock().unwrap().push(request);
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(action: &str) -> TransportRequest {
+        TransportRequest::new(vec![
+            ("KEY".to_string(), "secret".to_string()),
+            ("ACTION".to_string(), action.to_string()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_memory_transport_handler() {
+        let transport = MemoryTransport::new(|request| {
+            Ok(format!(
+                "RESULT=OK&ACTION={}",
+                request.param("ACTION").unwrap()
+            ))
+        });
+
+        let body = transport.send(request("STATUS")).await.unwrap();
+        assert_eq!(body, "RESULT=OK&ACTION=STATUS");
+        assert_eq!(transport.requests(), vec![request("STATUS")]);
+        assert_eq!(transport.requests()[0].param("MISSING"), None);
+    }
+
+    #[test]
+    fn test_request_params_and_headers() {
+        let mut request = request("FETCH");
+        request.set_param("ACTION", "STATUS");
+        request.set_param("OPTION", "ALL");
+        request.add_header("X-Trace", "abc");
+
+        assert_eq!(request.action(), Some("STATUS"));
+        assert_eq!(request.param("OPTION"), Some("ALL"));
+        assert_eq!(request.params.len(), 3);
+        assert_eq!(request.header("x-trace"), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_memory_transport_canned_responses() {
+        let transport = MemoryTransport::with_responses(["RESULT=OK", "RESULT=AUTH"]);
+        let shared = Arc::new(transport.clone());
+
+        assert_eq!(shared.send(request("STATUS")).await.unwrap(), "RESULT=OK");
+        assert_eq!(
+            transport.send(request("FETCH")).await.unwrap(),
+            "RESULT=AUTH"
+        );
+        assert!(transport.send(request("FETCH")).await.is_err());
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let mut request = request("STATUS");
+        request.add_header("Proxy-Authorization", "Basic hunter2");
+        let debug = format!("{:?}", request);
+        assert!(!debug.contains("secret"));
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("Proxy-Authorization"));
+        assert!(debug.contains("[REDACTED]"));
+        assert!(debug.contains("STATUS"));
+    }
+}
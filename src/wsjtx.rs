@@ -0,0 +1,722 @@
+//! WSJT-X UDP protocol support
+//!
+//! WSJT-X (and compatible programs such as JTDX) broadcast their state over
+//! UDP using a Qt `QDataStream` encoded binary protocol. [`WsjtxListener`]
+//! receives these datagrams, decodes the messages relevant to logging and
+//! uploads every `Logged ADIF` message through [`QrzLogbookClient::insert_qso`].
+//!
+//! # Example
+//! ```rust,no_run
+//! use qrz_logbook_api::{wsjtx::WsjtxListener, QrzLogbookClient};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "MyApp/1.0.0 (YOURCALL)")?;
+//! let listener = WsjtxListener::bind("127.0.0.1:2237").await?;
+//!
+//! listener
+//!     .run(&client, |qso, result| match result {
+//!         Ok(response) => println!("Uploaded {} as logid {}", qso.call, response.logid),
+//!         Err(e) => eprintln!("Failed to upload {}: {}", qso.call, e),
+//!     })
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    adif::AdifParser,
+    client::QrzLogbookClient,
+    error::{QrzLogbookError, QrzLogbookResult},
+    models::{InsertResponse, QsoRecord},
+};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::{
+    net::{ToSocketAddrs, UdpSocket},
+    sync::Mutex,
+};
+
+/// Magic number at the start of every WSJT-X datagram
+pub const MAGIC: u32 = 0xadbc_cbda;
+
+/// Default UDP port WSJT-X sends to
+pub const DEFAULT_PORT: u16 = 2237;
+
+const HEARTBEAT: u32 = 0;
+const STATUS: u32 = 1;
+const QSO_LOGGED: u32 = 5;
+const LOGGED_ADIF: u32 = 12;
+
+/// Julian day number of 0001-01-01 minus one, the CE day-count origin
+const JULIAN_DAY_CE_OFFSET: i64 = 1_721_425;
+
+/// Decoded WSJT-X message
+#[derive(Debug, Clone, PartialEq)]
+pub enum WsjtxMessage {
+    Heartbeat(Heartbeat),
+    Status(Status),
+    QsoLogged(QsoLogged),
+    LoggedAdif(LoggedAdif),
+    /// Valid message of a type this module does not decode
+    Other {
+        id: String,
+        message_type: u32,
+    },
+}
+
+impl WsjtxMessage {
+    /// Decode a single WSJT-X datagram
+    pub fn decode(datagram: &[u8]) -> QrzLogbookResult<Self> {
+        let mut reader = Reader::new(datagram);
+
+        let magic = reader.u32()?;
+        if magic != MAGIC {
+            return Err(QrzLogbookError::protocol(format!(
+                "Invalid WSJT-X magic number: {:#010x}",
+                magic
+            )));
+        }
+
+        let _schema = reader.u32()?;
+        let message_type = reader.u32()?;
+        let id = reader.utf8()?;
+
+        let message = match message_type {
+            HEARTBEAT => Self::Heartbeat(Heartbeat {
+                id,
+                max_schema: reader.u32()?,
+                version: reader.utf8()?,
+                revision: reader.utf8()?,
+            }),
+            STATUS => Self::Status(Status::decode(id, &mut reader)?),
+            QSO_LOGGED => Self::QsoLogged(QsoLogged::decode(id, &mut reader)?),
+            LOGGED_ADIF => Self::LoggedAdif(LoggedAdif {
+                id,
+                adif: reader.utf8()?,
+            }),
+            message_type => Self::Other { id, message_type },
+        };
+
+        Ok(message)
+    }
+
+    /// Id of the WSJT-X instance that sent the message
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Heartbeat(m) => &m.id,
+            Self::Status(m) => &m.id,
+            Self::QsoLogged(m) => &m.id,
+            Self::LoggedAdif(m) => &m.id,
+            Self::Other { id, .. } => id,
+        }
+    }
+}
+
+/// Heartbeat (type 0), sent periodically by every WSJT-X instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heartbeat {
+    pub id: String,
+    pub max_schema: u32,
+    pub version: String,
+    pub revision: String,
+}
+
+/// Status (type 1), sent whenever the WSJT-X state changes
+///
+/// Fields added in later schema revisions are `None` when the sender
+/// doesn't include them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Status {
+    pub id: String,
+    /// Dial frequency in Hz
+    pub dial_frequency: u64,
+    pub mode: String,
+    pub dx_call: String,
+    pub report: String,
+    pub tx_mode: String,
+    pub tx_enabled: bool,
+    pub transmitting: bool,
+    pub decoding: bool,
+    pub rx_df: u32,
+    pub tx_df: u32,
+    pub de_call: String,
+    pub de_grid: String,
+    pub dx_grid: String,
+    pub tx_watchdog: bool,
+    pub sub_mode: String,
+    pub fast_mode: bool,
+    pub special_operation_mode: Option<u8>,
+    pub frequency_tolerance: Option<u32>,
+    pub tr_period: Option<u32>,
+    pub configuration_name: Option<String>,
+    pub tx_message: Option<String>,
+}
+
+impl Status {
+    fn decode(id: String, reader: &mut Reader<'_>) -> QrzLogbookResult<Self> {
+        Ok(Self {
+            id,
+            dial_frequency: reader.u64()?,
+            mode: reader.utf8()?,
+            dx_call: reader.utf8()?,
+            report: reader.utf8()?,
+            tx_mode: reader.utf8()?,
+            tx_enabled: reader.bool()?,
+            transmitting: reader.bool()?,
+            decoding: reader.bool()?,
+            rx_df: reader.u32()?,
+            tx_df: reader.u32()?,
+            de_call: reader.utf8()?,
+            de_grid: reader.utf8()?,
+            dx_grid: reader.utf8()?,
+            tx_watchdog: reader.bool()?,
+            sub_mode: reader.utf8()?,
+            fast_mode: reader.bool()?,
+            special_operation_mode: reader.optional(Reader::u8)?,
+            frequency_tolerance: reader.optional(Reader::u32)?,
+            tr_period: reader.optional(Reader::u32)?,
+            configuration_name: reader.optional(Reader::utf8)?,
+            tx_message: reader.optional(Reader::utf8)?,
+        })
+    }
+}
+
+/// QSO Logged (type 5), sent when the operator accepts the log QSO dialog
+#[derive(Debug, Clone, PartialEq)]
+pub struct QsoLogged {
+    pub id: String,
+    /// End of the QSO in UTC
+    pub date_time_off: Option<NaiveDateTime>,
+    pub dx_call: String,
+    pub dx_grid: String,
+    /// Transmit frequency in Hz
+    pub tx_frequency: u64,
+    pub mode: String,
+    pub report_sent: String,
+    pub report_received: String,
+    pub tx_power: String,
+    pub comments: String,
+    pub name: String,
+    /// Start of the QSO in UTC
+    pub date_time_on: Option<NaiveDateTime>,
+    pub operator_call: String,
+    pub my_call: String,
+    pub my_grid: String,
+    pub exchange_sent: String,
+    pub exchange_received: String,
+    pub propagation_mode: Option<String>,
+}
+
+impl QsoLogged {
+    fn decode(id: String, reader: &mut Reader<'_>) -> QrzLogbookResult<Self> {
+        Ok(Self {
+            id,
+            date_time_off: reader.date_time()?,
+            dx_call: reader.utf8()?,
+            dx_grid: reader.utf8()?,
+            tx_frequency: reader.u64()?,
+            mode: reader.utf8()?,
+            report_sent: reader.utf8()?,
+            report_received: reader.utf8()?,
+            tx_power: reader.utf8()?,
+            comments: reader.utf8()?,
+            name: reader.utf8()?,
+            date_time_on: reader.date_time()?,
+            operator_call: reader.utf8()?,
+            my_call: reader.utf8()?,
+            my_grid: reader.utf8()?,
+            exchange_sent: reader.utf8()?,
+            exchange_received: reader.utf8()?,
+            propagation_mode: reader.optional(Reader::utf8)?,
+        })
+    }
+}
+
+/// Logged ADIF (type 12), the ADIF record WSJT-X wrote to its own log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedAdif {
+    pub id: String,
+    pub adif: String,
+}
+
+impl LoggedAdif {
+    /// Parse the logged ADIF into QSO records
+    pub fn to_qso_records(&self) -> QrzLogbookResult<Vec<QsoRecord>> {
+        AdifParser::parse_adif(&self.adif)
+    }
+}
+
+/// Listener receiving WSJT-X UDP datagrams
+pub struct WsjtxListener {
+    socket: UdpSocket,
+    buf: Mutex<Vec<u8>>,
+}
+
+impl WsjtxListener {
+    /// Bind a UDP socket for WSJT-X messages (WSJT-X sends to port 2237 by default)
+    pub async fn bind(addr: impl ToSocketAddrs) -> QrzLogbookResult<Self> {
+        let socket = UdpSocket::bind(addr).await?;
+        Ok(Self {
+            socket,
+            buf: Mutex::new(vec![0u8; 65536]),
+        })
+    }
+
+    /// Join a multicast group, for WSJT-X configured with a multicast UDP server
+    pub fn join_multicast_v4(
+        &self,
+        multiaddr: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> QrzLogbookResult<()> {
+        Ok(self.socket.join_multicast_v4(multiaddr, interface)?)
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> QrzLogbookResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Receive and decode the next datagram
+    pub async fn recv(&self) -> QrzLogbookResult<WsjtxMessage> {
+        let mut buf = self.buf.lock().await;
+        let (len, _) = self.socket.recv_from(&mut buf).await?;
+        WsjtxMessage::decode(&buf[..len])
+    }
+
+    /// Wait for the next `Logged ADIF` message and parse its QSOs
+    ///
+    /// Other messages and datagrams that aren't valid WSJT-X messages are skipped.
+    pub async fn recv_logged_qsos(&self) -> QrzLogbookResult<Vec<QsoRecord>> {
+        loop {
+            match self.recv().await {
+                Ok(WsjtxMessage::LoggedAdif(logged)) => return logged.to_qso_records(),
+                Ok(_) | Err(QrzLogbookError::Protocol(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Wait for the next logged QSOs and insert them into the logbook
+    ///
+    /// Returns each QSO along with the result of its upload.
+    pub async fn upload_next(
+        &self,
+        client: &QrzLogbookClient,
+    ) -> QrzLogbookResult<Vec<(QsoRecord, QrzLogbookResult<InsertResponse>)>> {
+        let qsos = self.recv_logged_qsos().await?;
+        let mut results = Vec::with_capacity(qsos.len());
+        for qso in qsos {
+            let result = client.insert_qso(&qso, false).await;
+            results.push((qso, result));
+        }
+        Ok(results)
+    }
+
+    /// Upload every logged QSO until a socket error occurs
+    ///
+    /// `on_upload` is called with each QSO and the result of its upload.
+    /// ADIF that can't be parsed is skipped.
+    pub async fn run<F>(&self, client: &QrzLogbookClient, mut on_upload: F) -> QrzLogbookResult<()>
+    where
+        F: FnMut(&QsoRecord, &QrzLogbookResult<InsertResponse>),
+    {
+        loop {
+            match self.upload_next(client).await {
+                Ok(results) => {
+                    for (qso, result) in &results {
+                        on_upload(qso, result);
+                    }
+                }
+                Err(QrzLogbookError::AdifParse(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Big-endian `QDataStream` reader
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> QrzLogbookResult<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| QrzLogbookError::protocol("Truncated WSJT-X message"))?;
+        let bytes = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> QrzLogbookResult<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> QrzLogbookResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> QrzLogbookResult<bool> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u32(&mut self) -> QrzLogbookResult<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> QrzLogbookResult<i32> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> QrzLogbookResult<u64> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> QrzLogbookResult<i64> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    /// `QByteArray` holding UTF-8; a null array decodes as an empty string
+    fn utf8(&mut self) -> QrzLogbookResult<String> {
+        let len = self.u32()?;
+        if len == u32::MAX {
+            return Ok(String::new());
+        }
+
+        let bytes = self.take(len as usize)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| QrzLogbookError::protocol("Invalid UTF-8 in WSJT-X message"))
+    }
+
+    /// `QDateTime` converted to UTC; `None` for a null date
+    fn date_time(&mut self) -> QrzLogbookResult<Option<NaiveDateTime>> {
+        let julian_day = self.i64()?;
+        let millis = self.u32()?;
+        let timespec = self.u8()?;
+
+        let offset_seconds = match timespec {
+            // Local time and UTC; WSJT-X always sends UTC
+            0 | 1 => 0,
+            2 => self.i32()?,
+            _ => {
+                return Err(QrzLogbookError::protocol(format!(
+                    "Unsupported QDateTime timespec {}",
+                    timespec
+                )))
+            }
+        };
+
+        if julian_day == 0 || millis == u32::MAX {
+            return Ok(None);
+        }
+
+        let date = i32::try_from(julian_day - JULIAN_DAY_CE_OFFSET)
+            .ok()
+            .and_then(NaiveDate::from_num_days_from_ce_opt)
+            .ok_or_else(|| QrzLogbookError::protocol("Invalid date in WSJT-X message"))?;
+        let time = NaiveTime::from_num_seconds_from_midnight_opt(
+            millis / 1000,
+            (millis % 1000) * 1_000_000,
+        )
+        .ok_or_else(|| QrzLogbookError::protocol("Invalid time in WSJT-X message"))?;
+
+        Ok(Some(
+            date.and_time(time) - Duration::seconds(offset_seconds.into()),
+        ))
+    }
+
+    /// Read a trailing field that older schema revisions don't send
+    fn optional<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> QrzLogbookResult<T>,
+    ) -> QrzLogbookResult<Option<T>> {
+        if self.pos >= self.buf.len() {
+            Ok(None)
+        } else {
+            read(self).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MemoryTransport;
+    use chrono::Datelike;
+    use tokio::sync::Notify;
+
+    /// Minimal `QDataStream` writer for building synthetic datagrams
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn new(message_type: u32, id: &str) -> Self {
+            let mut writer = Self(Vec::new());
+            writer.u32(MAGIC).u32(3).u32(message_type).utf8(id);
+            writer
+        }
+
+        fn u8(&mut self, value: u8) -> &mut Self {
+            self.0.push(value);
+            self
+        }
+
+        fn u32(&mut self, value: u32) -> &mut Self {
+            self.0.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        fn u64(&mut self, value: u64) -> &mut Self {
+            self.0.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        fn utf8(&mut self, value: &str) -> &mut Self {
+            self.u32(value.len() as u32);
+            self.0.extend_from_slice(value.as_bytes());
+            self
+        }
+
+        fn date_time(&mut self, value: NaiveDateTime) -> &mut Self {
+            let julian_day = value.date().num_days_from_ce() as i64 + JULIAN_DAY_CE_OFFSET;
+            self.0.extend_from_slice(&julian_day.to_be_bytes());
+            let millis = value.time().signed_duration_since(NaiveTime::MIN);
+            self.u32(millis.num_milliseconds() as u32).u8(1)
+        }
+    }
+
+    const LOGGED_ADIF_TEXT: &str = "\n<adif_ver:5>3.1.0\n<programid:6>WSJT-X\n<EOH>\n<call:5>JA1XY <gridsquare:4>PM95 <mode:3>FT8 <rst_sent:3>-10 <rst_rcvd:3>-15 <qso_date:8>20240315 <time_on:6>123015 <qso_date_off:8>20240315 <time_off:6>123130 <band:3>20m <freq:9>14.075500 <station_callsign:5>K1ABC <my_gridsquare:4>FN42 <eor>";
+
+    fn logged_adif_datagram() -> Vec<u8> {
+        let mut writer = Writer::new(LOGGED_ADIF, "WSJT-X");
+        writer.utf8(LOGGED_ADIF_TEXT);
+        writer.0
+    }
+
+    #[test]
+    fn test_decode_heartbeat() {
+        let mut writer = Writer::new(HEARTBEAT, "WSJT-X");
+        writer.u32(3).utf8("2.6.1").utf8("abc123");
+
+        let message = WsjtxMessage::decode(&writer.0).unwrap();
+        assert_eq!(
+            message,
+            WsjtxMessage::Heartbeat(Heartbeat {
+                id: "WSJT-X".to_string(),
+                max_schema: 3,
+                version: "2.6.1".to_string(),
+                revision: "abc123".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_status_without_optional_fields() {
+        let mut writer = Writer::new(STATUS, "WSJT-X");
+        writer
+            .u64(14_074_000)
+            .utf8("FT8")
+            .utf8("JA1XY")
+            .utf8("-10")
+            .utf8("FT8")
+            .u8(1)
+            .u8(0)
+            .u8(1)
+            .u32(1500)
+            .u32(1200)
+            .utf8("K1ABC")
+            .utf8("FN42")
+            .utf8("PM95")
+            .u8(0)
+            .utf8("")
+            .u8(0);
+
+        let WsjtxMessage::Status(status) = WsjtxMessage::decode(&writer.0).unwrap() else {
+            panic!("Expected status message");
+        };
+        assert_eq!(status.dial_frequency, 14_074_000);
+        assert_eq!(status.dx_call, "JA1XY");
+        assert!(status.tx_enabled);
+        assert!(!status.transmitting);
+        assert_eq!(status.de_call, "K1ABC");
+        assert_eq!(status.tr_period, None);
+        assert_eq!(status.tx_message, None);
+    }
+
+    #[test]
+    fn test_decode_qso_logged() {
+        let on = NaiveDate::from_ymd_opt(2024, 3, 15)
+            .unwrap()
+            .and_hms_opt(12, 30, 15)
+            .unwrap();
+        let off = on + Duration::seconds(75);
+
+        let mut writer = Writer::new(QSO_LOGGED, "WSJT-X");
+        writer
+            .date_time(off)
+            .utf8("JA1XY")
+            .utf8("PM95")
+            .u64(14_075_500)
+            .utf8("FT8")
+            .utf8("-10")
+            .utf8("-15")
+            .utf8("100")
+            .utf8("")
+            .utf8("")
+            .date_time(on)
+            .utf8("")
+            .utf8("K1ABC")
+            .utf8("FN42")
+            .utf8("")
+            .utf8("");
+
+        let WsjtxMessage::QsoLogged(logged) = WsjtxMessage::decode(&writer.0).unwrap() else {
+            panic!("Expected QSO logged message");
+        };
+        assert_eq!(logged.date_time_on, Some(on));
+        assert_eq!(logged.date_time_off, Some(off));
+        assert_eq!(logged.dx_call, "JA1XY");
+        assert_eq!(logged.tx_frequency, 14_075_500);
+        assert_eq!(logged.my_call, "K1ABC");
+        assert_eq!(logged.propagation_mode, None);
+    }
+
+    #[test]
+    fn test_decode_logged_adif() {
+        let WsjtxMessage::LoggedAdif(logged) =
+            WsjtxMessage::decode(&logged_adif_datagram()).unwrap()
+        else {
+            panic!("Expected logged ADIF message");
+        };
+
+        let qsos = logged.to_qso_records().unwrap();
+        assert_eq!(qsos.len(), 1);
+        assert_eq!(qsos[0].call, "JA1XY");
+        assert_eq!(qsos[0].station_callsign, "K1ABC");
+        assert_eq!(qsos[0].mode, "FT8");
+        assert_eq!(
+            qsos[0].time_on,
+            NaiveTime::from_hms_opt(12, 30, 15).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_datagrams() {
+        assert!(matches!(
+            WsjtxMessage::decode(&[0, 1, 2, 3, 4, 5, 6, 7]),
+            Err(QrzLogbookError::Protocol(_))
+        ));
+
+        let truncated = &logged_adif_datagram()[..30];
+        assert!(matches!(
+            WsjtxMessage::decode(truncated),
+            Err(QrzLogbookError::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_unknown_message_type() {
+        let writer = Writer::new(2, "WSJT-X");
+        let message = WsjtxMessage::decode(&writer.0).unwrap();
+        assert_eq!(
+            message,
+            WsjtxMessage::Other {
+                id: "WSJT-X".to_string(),
+                message_type: 2
+            }
+        );
+        assert_eq!(message.id(), "WSJT-X");
+    }
+
+    #[tokio::test]
+    async fn test_listener_receives_logged_qsos() {
+        let listener = WsjtxListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut heartbeat = Writer::new(HEARTBEAT, "WSJT-X");
+        heartbeat.u32(3).utf8("2.6.1").utf8("abc123");
+        sender.send_to(&heartbeat.0, addr).await.unwrap();
+        sender.send_to(b"not wsjt-x", addr).await.unwrap();
+        sender.send_to(&logged_adif_datagram(), addr).await.unwrap();
+
+        let qsos = listener.recv_logged_qsos().await.unwrap();
+        assert_eq!(qsos.len(), 1);
+        assert_eq!(qsos[0].call, "JA1XY");
+    }
+
+    fn client_with(transport: &MemoryTransport) -> QrzLogbookClient {
+        QrzLogbookClient::with_transport(
+            "ABCD-1234-EF56-7890",
+            "TestApp/1.0.0 (N0CALL)",
+            transport.clone(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upload_next() {
+        let transport = MemoryTransport::with_responses(["RESULT=OK&LOGID=42&COUNT=1"]);
+        let client = client_with(&transport);
+        let listener = WsjtxListener::bind("127.0.0.1:0").await.unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        sender
+            .send_to(&logged_adif_datagram(), listener.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        let results = listener.upload_next(&client).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.call, "JA1XY");
+        assert_eq!(results[0].1.as_ref().unwrap().logid, 42);
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].action(), Some("INSERT"));
+        assert!(requests[0].param("ADIF").unwrap().contains("JA1XY"));
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_uploads() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&LOGID=42&COUNT=1",
+            "RESULT=FAIL&REASON=duplicate",
+        ]);
+        let client = client_with(&transport);
+        let listener = WsjtxListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut bad_adif = Writer::new(LOGGED_ADIF, "WSJT-X");
+        bad_adif.utf8("<call:4>W1AW<eor>");
+        sender.send_to(&bad_adif.0, addr).await.unwrap();
+        sender.send_to(&logged_adif_datagram(), addr).await.unwrap();
+        sender.send_to(&logged_adif_datagram(), addr).await.unwrap();
+
+        let done = Notify::new();
+        let mut uploads = Vec::new();
+        tokio::select! {
+            result = listener.run(&client, |qso, result| {
+                uploads.push((qso.call.clone(), result.as_ref().map(|r| r.logid).ok()));
+                if uploads.len() == 2 {
+                    done.notify_one();
+                }
+            }) => panic!("run returned {:?}", result),
+            _ = done.notified() => {}
+        }
+
+        assert_eq!(
+            uploads,
+            vec![("JA1XY".to_string(), Some(42)), ("JA1XY".to_string(), None)]
+        );
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
@@ -1,11 +1,13 @@
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
 use qrz_logbook_api::{
-    adif::AdifParser, FetchOptions, QrzLogbookClient, QrzLogbookError, QsoRecord,
+    adif::{AdifParser, TimePrecision},
+    CallsignPolicy, DeleteOptions, FetchOptions, FetchStatus, FetchType, QrzLogbookClient,
+    QrzLogbookError, QrzLogbookResult, QsoPatch, QsoRecord,
 };
 
 #[tokio::test]
 async fn test_client_creation_valid() {
-    let client = QrzLogbookClient::new("valid-api-key-12345", "TestApp/1.0.0 (N0CALL)");
+    let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestApp/1.0.0 (N0CALL)");
     assert!(client.is_ok());
 }
 
@@ -13,17 +15,23 @@ async fn test_client_creation_valid() {
 async fn test_client_creation_invalid_key() {
     let result = QrzLogbookClient::new("", "TestApp/1.0.0 (N0CALL)");
     assert!(matches!(result, Err(QrzLogbookError::InvalidKey)));
+
+    let result = QrzLogbookClient::new("not-a-qrz-api-key", "TestApp/1.0.0 (N0CALL)");
+    assert!(matches!(result, Err(QrzLogbookError::InvalidKey)));
+
+    let result = QrzLogbookClient::new(" abcd-1234-ef56-7890 ", "TestApp/1.0.0 (N0CALL)");
+    assert!(result.is_ok());
 }
 
 #[tokio::test]
 async fn test_client_creation_invalid_user_agent() {
-    let result = QrzLogbookClient::new("valid-api-key-12345", "python-requests");
+    let result = QrzLogbookClient::new("ABCD-1234-EF56-7890", "python-requests");
     assert!(matches!(result, Err(QrzLogbookError::InvalidUserAgent)));
 
-    let result = QrzLogbookClient::new("valid-api-key-12345", "node-fetch");
+    let result = QrzLogbookClient::new("ABCD-1234-EF56-7890", "node-fetch");
     assert!(matches!(result, Err(QrzLogbookError::InvalidUserAgent)));
 
-    let result = QrzLogbookClient::new("valid-api-key-12345", "");
+    let result = QrzLogbookClient::new("ABCD-1234-EF56-7890", "");
     assert!(matches!(result, Err(QrzLogbookError::InvalidUserAgent)));
 }
 
@@ -95,6 +103,58 @@ fn test_fetch_options_date_range() {
     assert!(option_string.contains("DATETO:20241231"));
 }
 
+#[test]
+fn test_fetch_options_extended() {
+    let options = FetchOptions::new()
+        .status(FetchStatus::Confirmed)
+        .modified_since(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap())
+        .dxcc(291)
+        .fetch_type(FetchType::Logids);
+
+    let option_string = options.to_option_string();
+    assert!(option_string.contains("STATUS:CONFIRMED"));
+    assert!(option_string.contains("MODSINCE:2024-06-01"));
+    assert!(option_string.contains("DXCC:291"));
+    assert!(option_string.contains("TYPE:LOGIDS"));
+    assert!(options.validate().is_ok());
+
+    let options = FetchOptions::new().max(10).logids([101, 102, 103]);
+    assert_eq!(options.to_option_string(), "MAX:10,LOGIDS:101,102,103");
+    assert!(options.validate().is_ok());
+}
+
+#[test]
+fn test_fetch_options_validation() {
+    let invalid = [
+        FetchOptions::new().band("20m").logids([1]),
+        FetchOptions::all().logids([1]),
+        FetchOptions::new()
+            .logids([1])
+            .fetch_type(FetchType::Logids),
+        FetchOptions::new().date_range(
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        ),
+    ];
+
+    for options in &invalid {
+        assert!(
+            matches!(options.validate(), Err(QrzLogbookError::InvalidParams(_))),
+            "{}",
+            options.to_option_string()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_qsos_rejects_invalid_options() {
+    let client = QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestApp/1.0.0 (TEST)").unwrap();
+    let result = client
+        .fetch_qsos(&FetchOptions::new().call("W1AW").logids([1]))
+        .await;
+    assert!(matches!(result, Err(QrzLogbookError::InvalidParams(_))));
+}
+
 #[test]
 fn test_adif_generation() {
     let qso = QsoRecord::builder()
@@ -217,14 +277,199 @@ fn test_adif_roundtrip() {
     assert_eq!(parsed_qso.name, original_qso.name);
     assert_eq!(parsed_qso.qth, original_qso.qth);
     assert_eq!(parsed_qso.comment, original_qso.comment);
+    // Known ADIF fields come back as typed fields
+    assert_eq!(parsed_qso.gridsquare.as_deref(), Some("FN42aa"));
+    assert!(parsed_qso.additional_fields.is_empty());
+}
+
+#[test]
+fn test_adif_roundtrip_preserves_seconds() {
+    let qso = QsoRecord::builder()
+        .call("JA1XY")
+        .station_callsign("K1ABC")
+        .date(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap())
+        .time_on(NaiveTime::from_hms_opt(14, 30, 27).unwrap())
+        .band("20m")
+        .mode("FT8")
+        .build();
+
+    let adif = AdifParser::to_adif(&qso);
+    assert!(adif.contains("<time_on:6>143027"));
+    let parsed = AdifParser::parse_adif(&adif).unwrap();
+    assert_eq!(parsed[0].time_on, qso.time_on);
+
+    let adif = AdifParser::to_adif_with_precision(&qso, TimePrecision::Minutes);
+    let parsed = AdifParser::parse_adif(&adif).unwrap();
     assert_eq!(
-        parsed_qso.additional_fields.get("gridsquare"),
-        Some(&"FN42aa".to_string())
+        parsed[0].time_on,
+        NaiveTime::from_hms_opt(14, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_qso_patch_apply() {
+    let qso = QsoRecord::builder()
+        .call("W1AW")
+        .station_callsign("K1ABC")
+        .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+        .band("20m")
+        .mode("SSB")
+        .comment("old comment")
+        .build();
+
+    let patched = QsoPatch::new()
+        .set("NAME", "Hiram")
+        .set("rst_rcvd", "579")
+        .clear("comment")
+        .apply(&qso)
+        .unwrap();
+    assert_eq!(patched.name.as_deref(), Some("Hiram"));
+    assert_eq!(patched.rst_rcvd.as_deref(), Some("579"));
+    assert_eq!(patched.comment, None);
+    assert_eq!(patched.call, "W1AW");
+
+    assert!(QsoPatch::new().clear("call").apply(&qso).is_err());
+    assert!(QsoPatch::new()
+        .set("qso_date", "2024-01-15")
+        .apply(&qso)
+        .is_err());
+}
+
+#[test]
+fn test_qso_diff() {
+    let server = QsoRecord::builder()
+        .call("W1AW")
+        .station_callsign("K1ABC")
+        .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+        .band("20m")
+        .mode("FT8")
+        .freq(14.074)
+        .comment("tnx")
+        .additional_field("FREQ_RX", "14.0740")
+        .additional_field("MY_RIG", "IC-7300")
+        .build();
+    let mut local = server.clone();
+    local.freq = Some(14.074000);
+    local.comment = None;
+    local.name = Some("Hiram".to_string());
+    local.additional_fields.clear();
+    local
+        .additional_fields
+        .insert("freq_rx".to_string(), "14.074".to_string());
+    local
+        .additional_fields
+        .insert("my_rig".to_string(), "FT-991A".to_string());
+
+    assert!(server.diff(&server).is_empty());
+
+    let diff = server.diff(&local);
+    assert_eq!(
+        diff.fields().collect::<Vec<_>>(),
+        vec!["comment", "my_rig", "name"]
+    );
+    assert_eq!(diff.get("COMMENT").unwrap().old.as_deref(), Some("tnx"));
+    assert_eq!(diff.get("comment").unwrap().new, None);
+    assert_eq!(diff.get("name").unwrap().old, None);
+    assert_eq!(diff.get("my_rig").unwrap().new.as_deref(), Some("FT-991A"));
+    assert_eq!(
+        diff.to_string(),
+        "comment: tnx -> (unset)\nmy_rig: IC-7300 -> FT-991A\nname: (unset) -> Hiram\n"
+    );
+
+    let patched = diff.to_patch().apply(&server).unwrap();
+    assert!(patched.diff(&local).is_empty());
+}
+
+#[test]
+fn test_qso_patch_json() {
+    let patch = QsoPatch::new().set("name", "Hiram").clear("comment");
+    let json = serde_json::to_string(&patch).unwrap();
+    assert_eq!(json, r#"{"comment":null,"name":"Hiram"}"#);
+
+    let parsed: QsoPatch = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, patch);
+
+    let qso = QsoRecord::builder()
+        .call("W1AW")
+        .station_callsign("K1ABC")
+        .comment("tnx")
+        .build();
+    let audit: QsoPatch = serde_json::from_str(r#"{"NAME":"Hiram","Comment":null}"#).unwrap();
+    assert_eq!(audit, patch);
+    let patched = audit.apply(&qso).unwrap();
+    assert_eq!(patched.name.as_deref(), Some("Hiram"));
+    assert_eq!(patched.comment, None);
+    assert!(patched.additional_fields.is_empty());
+
+    let mut raw = QsoPatch::new();
+    raw.changes
+        .insert("NAME".to_string(), Some("Hiram".to_string()));
+    assert_eq!(raw.apply(&qso).unwrap().name.as_deref(), Some("Hiram"));
+}
+
+#[test]
+fn test_qso_midnight_rollover() {
+    let qso = QsoRecord::builder()
+        .call("W1AW")
+        .station_callsign("K1ABC")
+        .date(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap())
+        .time_on(NaiveTime::from_hms_opt(23, 58, 0).unwrap())
+        .time_off(NaiveTime::from_hms_opt(0, 3, 0).unwrap())
+        .band("20m")
+        .mode("CW")
+        .build();
+
+    assert_eq!(
+        qso.start_utc(),
+        Utc.with_ymd_and_hms(2024, 12, 31, 23, 58, 0).unwrap()
+    );
+    assert_eq!(
+        qso.end_utc(),
+        Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 3, 0).unwrap())
     );
+    assert_eq!(qso.duration(), Some(Duration::minutes(5)));
+
+    let adif = AdifParser::to_adif(&qso);
+    assert!(adif.contains("<qso_date_off:8>20250101"));
+
+    let parsed = &AdifParser::parse_adif(&adif).unwrap()[0];
+    assert_eq!(parsed.qso_date_off, NaiveDate::from_ymd_opt(2025, 1, 1));
+    assert_eq!(parsed.duration(), Some(Duration::minutes(5)));
 }
 
-// Mock tests would require a mock server setup
-// For now, we'll focus on unit tests and provide examples for integration testing
+#[test]
+fn test_qso_same_day_has_no_date_off() {
+    let qso = QsoRecord::builder()
+        .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+        .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+        .time_off(NaiveTime::from_hms_opt(14, 45, 0).unwrap())
+        .build();
+
+    assert_eq!(qso.duration(), Some(Duration::minutes(15)));
+    assert!(!AdifParser::to_adif(&qso).contains("qso_date_off"));
+    assert_eq!(QsoRecord::builder().build().duration(), None);
+}
+
+#[test]
+fn test_qso_builder_with_timezone() {
+    // 19:58 local in UTC-4 is 23:58 UTC
+    let edt = FixedOffset::west_opt(4 * 3600).unwrap();
+    let qso = QsoRecord::builder()
+        .start(edt.with_ymd_and_hms(2024, 7, 4, 19, 58, 0).unwrap())
+        .end(edt.with_ymd_and_hms(2024, 7, 4, 20, 10, 30).unwrap())
+        .build();
+
+    assert_eq!(qso.qso_date, NaiveDate::from_ymd_opt(2024, 7, 4).unwrap());
+    assert_eq!(qso.time_on, NaiveTime::from_hms_opt(23, 58, 0).unwrap());
+    assert_eq!(qso.qso_date_off, NaiveDate::from_ymd_opt(2024, 7, 5));
+    assert_eq!(qso.time_off, NaiveTime::from_hms_opt(0, 10, 30));
+    assert_eq!(qso.duration(), Some(Duration::seconds(12 * 60 + 30)));
+}
+
+// Response parsing tests against canned bodies. End-to-end tests against the
+// fake QRZ server are in `fake_server_tests` and need the `testing` feature.
 
 #[cfg(test)]
 mod mock_tests {
@@ -232,7 +477,7 @@ mod mock_tests {
 
     // Helper function to create a test client
     fn create_test_client() -> QrzLogbookClient {
-        QrzLogbookClient::new("test-api-key-12345", "TestSuite/1.0.0 (N0CALL)").unwrap()
+        QrzLogbookClient::new("ABCD-1234-EF56-7890", "TestSuite/1.0.0 (N0CALL)").unwrap()
     }
 
     #[test]
@@ -243,6 +488,18 @@ mod mock_tests {
 
         assert_eq!(result.logid, 130877825);
         assert_eq!(result.count, 1);
+        assert!(!result.replaced);
+    }
+
+    #[test]
+    fn test_response_parsing_insert_replace() {
+        let client = create_test_client();
+        let response = "RESULT=REPLACE&LOGID=130877825&COUNT=1".to_string();
+        let result = client.parse_insert_response(response).unwrap();
+
+        assert_eq!(result.logid, 130877825);
+        assert_eq!(result.count, 1);
+        assert!(result.replaced);
     }
 
     #[test]
@@ -307,6 +564,17 @@ mod mock_tests {
         assert_eq!(result.qsos[0].call, "W1AW");
     }
 
+    #[test]
+    fn test_response_parsing_fetch_logids_only() {
+        let client = create_test_client();
+        let response = "RESULT=OK&COUNT=3&LOGIDS=12345%2C12346%2C12350".to_string();
+        let result = client.parse_fetch_response(response).unwrap();
+
+        assert_eq!(result.count, 3);
+        assert_eq!(result.logids, vec![12345, 12346, 12350]);
+        assert!(result.qsos.is_empty());
+    }
+
     #[test]
     fn test_response_parsing_auth_error() {
         let client = create_test_client();
@@ -316,3 +584,830 @@ mod mock_tests {
         assert!(matches!(result, Err(QrzLogbookError::Auth)));
     }
 }
+
+mod transport_tests {
+    use super::*;
+    use qrz_logbook_api::{
+        metrics::{RecordingMetrics, REQUESTS_TOTAL, REQUEST_DURATION_SECONDS},
+        middleware::{Header, Middleware, ResponseRecorder},
+        query::QsoFilter,
+        sync::HighWaterMark,
+        transport::{MemoryTransport, TransportRequest},
+    };
+    use std::sync::Arc;
+
+    fn client_with(transport: &MemoryTransport) -> QrzLogbookClient {
+        QrzLogbookClient::with_transport(
+            "ABCD-1234-EF56-7890",
+            "TestSuite/1.0.0 (N0CALL)",
+            transport.clone(),
+        )
+        .unwrap()
+    }
+
+    fn fetch_page(logids: std::ops::Range<u64>) -> String {
+        let adif: String = logids
+            .clone()
+            .map(|id| format!("<call:6>W{:05}<station_callsign:5>K1ABC<qso_date:8>20240115<time_on:4>1430<band:3>20m<mode:2>CW<eor>", id))
+            .collect();
+        let logids: Vec<_> = logids.map(|id| id.to_string()).collect();
+        format!(
+            "RESULT=OK&COUNT={}&LOGIDS={}&ADIF={}",
+            logids.len(),
+            urlencoding::encode(&logids.join(",")),
+            urlencoding::encode(&adif)
+        )
+    }
+
+    fn option(request: &TransportRequest) -> &str {
+        request.param("OPTION").unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn test_insert_through_transport() {
+        let transport = MemoryTransport::with_responses(["RESULT=OK&LOGID=777&COUNT=1"]);
+        let client = client_with(&transport);
+
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band("20m")
+            .mode("SSB")
+            .build();
+        let result = client.insert_qso(&qso, true).await.unwrap();
+        assert_eq!(result.logid, 777);
+
+        let request = &transport.requests()[0];
+        assert_eq!(request.param("KEY"), Some("ABCD-1234-EF56-7890"));
+        assert_eq!(request.param("ACTION"), Some("INSERT"));
+        assert_eq!(request.param("OPTION"), Some("REPLACE"));
+        assert!(request.param("ADIF").unwrap().contains("<call:4>W1AW"));
+    }
+
+    #[tokio::test]
+    async fn test_update_qso_through_transport() {
+        let transport = MemoryTransport::with_responses([
+            fetch_page(500..501),
+            "RESULT=REPLACE&LOGID=500&COUNT=1".to_string(),
+            fetch_page(500..501),
+            "RESULT=REPLACE&LOGID=501&COUNT=1".to_string(),
+        ]);
+        let client = client_with(&transport);
+
+        let patch = QsoPatch::new().set("rst_sent", "599");
+        let result = client.update_qso(500, &patch).await.unwrap();
+        assert!(result.replaced);
+        assert_eq!(result.logid, 500);
+        assert_eq!(result.qso.rst_sent.as_deref(), Some("599"));
+
+        let requests = transport.requests();
+        assert_eq!(option(&requests[0]), "LOGIDS:500");
+        assert_eq!(requests[1].param("OPTION"), Some("REPLACE"));
+        let adif = requests[1].param("ADIF").unwrap();
+        assert!(adif.contains("<rst_sent:3>599"));
+        assert!(!adif.contains("app_qrzlog"));
+
+        let err = client.update_qso(500, &patch).await.unwrap_err();
+        assert!(err.to_string().contains("501"));
+    }
+
+    #[tokio::test]
+    async fn test_update_qso_deletes_old_record_when_replace_inserts() {
+        let transport = MemoryTransport::with_responses([
+            fetch_page(500..501),
+            "RESULT=OK&LOGID=502&COUNT=1".to_string(),
+            "RESULT=OK&COUNT=1".to_string(),
+            fetch_page(502..503),
+            "RESULT=OK&LOGID=503&COUNT=1".to_string(),
+            "RESULT=FAIL&REASON=delete failed".to_string(),
+        ]);
+        let client = client_with(&transport);
+        let patch = QsoPatch::new().set("rst_sent", "599");
+
+        let result = client.update_qso(500, &patch).await.unwrap();
+        assert!(!result.replaced);
+        assert_eq!(result.logid, 502);
+        assert!(result.delete_error.is_none());
+        let requests = transport.requests();
+        assert_eq!(requests[2].param("ACTION"), Some("DELETE"));
+        assert_eq!(requests[2].param("LOGIDS"), Some("500"));
+
+        let result = client.update_qso(502, &patch).await.unwrap();
+        assert_eq!(result.logid, 503);
+        assert!(matches!(
+            result.delete_error,
+            Some(QrzLogbookError::Api { reason }) if reason == "delete failed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_insert_time_precision() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&LOGID=1&COUNT=1",
+            "RESULT=OK&LOGID=2&COUNT=1",
+        ]);
+        let qso = QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band("20m")
+            .mode("SSB")
+            .build();
+
+        let seconds = client_with(&transport).with_time_precision(TimePrecision::Seconds);
+        seconds.insert_qso(&qso, false).await.unwrap();
+        let mut with_seconds = qso.clone();
+        with_seconds.time_on = NaiveTime::from_hms_opt(14, 30, 45).unwrap();
+        let minutes = client_with(&transport).with_time_precision(TimePrecision::Minutes);
+        minutes.insert_qso(&with_seconds, false).await.unwrap();
+
+        let requests = transport.requests();
+        assert!(requests[0]
+            .param("ADIF")
+            .unwrap()
+            .contains("<time_on:6>143000"));
+        assert!(requests[1]
+            .param("ADIF")
+            .unwrap()
+            .contains("<time_on:4>1430"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_all_qsos_pages() {
+        let transport = MemoryTransport::with_responses([fetch_page(1..251), fetch_page(251..261)]);
+        let client = client_with(&transport);
+
+        let qsos = client.fetch_all_qsos(&FetchOptions::all()).await.unwrap();
+        assert_eq!(qsos.len(), 260);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(option(&requests[0]), "ALL,MAX:250");
+        assert_eq!(option(&requests[1]), "ALL,MAX:250,AFTERLOGID:251");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logids_pages() {
+        let logids_page = |ids: std::ops::Range<u64>| {
+            let ids: Vec<_> = ids.map(|id| id.to_string()).collect();
+            format!("RESULT=OK&COUNT={}&LOGIDS={}", ids.len(), ids.join("%2C"))
+        };
+        let transport = MemoryTransport::with_responses([
+            logids_page(1..251),
+            logids_page(251..501),
+            logids_page(501..504),
+        ]);
+        let client = client_with(&transport);
+
+        let logids = client.fetch_logids(&FetchOptions::all()).await.unwrap();
+        assert_eq!(logids, (1..504).collect::<Vec<u64>>());
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(option(&requests[0]), "ALL,MAX:250,TYPE:LOGIDS");
+        assert_eq!(
+            option(&requests[1]),
+            "ALL,MAX:250,AFTERLOGID:251,TYPE:LOGIDS"
+        );
+        assert_eq!(
+            option(&requests[2]),
+            "ALL,MAX:250,AFTERLOGID:501,TYPE:LOGIDS"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logids_and_reconcile() {
+        let page: Vec<_> = (1..=250).map(|id| id.to_string()).collect();
+        let transport = MemoryTransport::with_responses([
+            format!("RESULT=OK&COUNT=250&LOGIDS={}", page.join("%2C")),
+            "RESULT=OK&COUNT=2&LOGIDS=300%2C301".to_string(),
+        ]);
+        let client = client_with(&transport);
+
+        let diff = client
+            .reconcile_logids((2..=250).chain([299]))
+            .await
+            .unwrap();
+        assert_eq!(diff.added, vec![1, 300, 301]);
+        assert_eq!(diff.removed, vec![299]);
+
+        let requests = transport.requests();
+        assert_eq!(option(&requests[0]), "ALL,MAX:250,TYPE:LOGIDS");
+        assert_eq!(
+            option(&requests[1]),
+            "ALL,MAX:250,AFTERLOGID:251,TYPE:LOGIDS"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_incremental() {
+        let transport = MemoryTransport::with_responses([fetch_page(1..3), fetch_page(2..3)]);
+        let client = client_with(&transport);
+        let mut mark = HighWaterMark::new();
+
+        assert_eq!(client.fetch_incremental(&mut mark).await.unwrap().len(), 2);
+        let since = mark.since().unwrap();
+        assert_eq!(client.fetch_incremental(&mut mark).await.unwrap().len(), 1);
+        assert!(mark.since().unwrap() >= since);
+
+        let requests = transport.requests();
+        assert!(option(&requests[0]).starts_with("ALL,"));
+        let day_before = since.date_naive().pred_opt().unwrap();
+        assert!(option(&requests[1]).contains(&format!("MODSINCE:{}", day_before)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_incremental_keeps_mark_on_error() {
+        let transport = MemoryTransport::with_responses(["RESULT=AUTH"]);
+        let client = client_with(&transport);
+        let mut mark = HighWaterMark::new();
+
+        assert!(matches!(
+            client.fetch_incremental(&mut mark).await,
+            Err(QrzLogbookError::Auth)
+        ));
+        assert_eq!(mark.since(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_matching_pushdown() {
+        let transport = MemoryTransport::with_responses([fetch_page(1..5)]);
+        let client = client_with(&transport);
+
+        let filter: QsoFilter = "band=20m and call in (W00001,W00003)".parse().unwrap();
+        let qsos = client.fetch_matching(&filter).await.unwrap();
+        let calls: Vec<_> = qsos.iter().map(|q| q.call.as_str()).collect();
+        assert_eq!(calls, vec!["W00001", "W00003"]);
+        assert_eq!(option(&transport.requests()[0]), "BAND:20m,MAX:250");
+    }
+
+    #[tokio::test]
+    async fn test_verify_key_sends_normalized_key() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&DATA=BOOKID%3D4321%26OWNER%3Dk1abc%26COUNT%3D10",
+            "RESULT=FAIL&REASON=access denied",
+            "RESULT=FAIL&REASON=invalid api key",
+        ]);
+        let client = QrzLogbookClient::with_transport(
+            "abcd1234ef567890",
+            "TestSuite/1.0.0 (N0CALL)",
+            transport.clone(),
+        )
+        .unwrap();
+
+        let info = client.verify_key().await.unwrap();
+        assert_eq!(info.callsign, "K1ABC");
+        assert_eq!(info.book_id.as_deref(), Some("4321"));
+        assert_eq!(
+            transport.requests()[0].param("KEY"),
+            Some("ABCD-1234-EF56-7890")
+        );
+
+        assert!(matches!(
+            client.verify_key().await,
+            Err(QrzLogbookError::KeyRejected)
+        ));
+
+        // Only STATUS failures from verify_key are treated as a rejected key
+        assert!(matches!(
+            client.delete_qsos(vec![1]).await,
+            Err(QrzLogbookError::Api { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_key_outage_is_not_rejection() {
+        let html = MemoryTransport::with_responses(["<html>Captive portal</html>"]);
+        assert!(matches!(
+            client_with(&html).verify_key().await,
+            Err(QrzLogbookError::Api { reason }) if reason == "Unexpected response format"
+        ));
+
+        let unavailable =
+            MemoryTransport::new(|_| Err(QrzLogbookError::api_error("HTTP status 503")));
+        assert!(matches!(
+            client_with(&unavailable).verify_key().await,
+            Err(QrzLogbookError::Api { reason }) if reason == "HTTP status 503"
+        ));
+    }
+
+    fn owner_logbook() -> MemoryTransport {
+        MemoryTransport::new(|request| match request.action() {
+            Some("STATUS") => Ok("RESULT=OK&DATA=OWNER%3DK1ABC%26BOOKID%3D1".to_string()),
+            Some("INSERT") => Ok("RESULT=OK&LOGID=1&COUNT=1".to_string()),
+            _ => Ok("RESULT=FAIL&REASON=unexpected".to_string()),
+        })
+    }
+
+    fn station_qso(station_callsign: &str) -> QsoRecord {
+        QsoRecord::builder()
+            .call("W1AW")
+            .station_callsign(station_callsign)
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band("20m")
+            .mode("SSB")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_callsign_policy_reject() {
+        let transport = owner_logbook();
+        let client = client_with(&transport).with_callsign_policy(CallsignPolicy::Reject);
+
+        let results = client
+            .insert_qsos(
+                &[
+                    station_qso("k1abc"),
+                    station_qso("VE3/K1ABC/P"),
+                    station_qso("W9XYZ"),
+                ],
+                false,
+            )
+            .await;
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(QrzLogbookError::CallsignMismatch { station_callsign, owner })
+                if station_callsign == "VE3/K1ABC/P" && owner == "K1ABC"
+        ));
+        assert!(results[2].is_err());
+
+        // The owner is looked up once, and rejected QSOs are never uploaded
+        let actions: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|r| r.action().unwrap().to_string())
+            .collect();
+        assert_eq!(actions, vec!["STATUS", "INSERT"]);
+    }
+
+    #[tokio::test]
+    async fn test_callsign_policy_rewrite() {
+        let transport = owner_logbook();
+        let client = client_with(&transport)
+            .with_callsign_policy(CallsignPolicy::Rewrite)
+            .with_logbook_owner("k1abc");
+
+        client
+            .insert_qso(&station_qso("VE3/K1ABC/P"), false)
+            .await
+            .unwrap();
+        assert!(client
+            .insert_qso(&station_qso("W9XYZ"), false)
+            .await
+            .is_err());
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        let uploaded = AdifParser::parse_adif(requests[0].param("ADIF").unwrap()).unwrap();
+        assert_eq!(uploaded[0].station_callsign, "K1ABC");
+        assert_eq!(uploaded[0].operator.as_deref(), Some("VE3/K1ABC/P"));
+
+        // Short callsigns keep their base when the portable prefix is as long
+        let short = client_with(&transport)
+            .with_callsign_policy(CallsignPolicy::Rewrite)
+            .with_logbook_owner("N1A");
+        short
+            .insert_qso(&station_qso("N1A/VE3"), false)
+            .await
+            .unwrap();
+        let uploaded =
+            AdifParser::parse_adif(transport.requests()[1].param("ADIF").unwrap()).unwrap();
+        assert_eq!(uploaded[0].station_callsign, "N1A");
+        assert_eq!(uploaded[0].operator.as_deref(), Some("N1A/VE3"));
+    }
+
+    #[tokio::test]
+    async fn test_callsign_policy_allow_skips_lookup() {
+        let transport = owner_logbook();
+        let client = client_with(&transport);
+
+        client
+            .insert_qso(&station_qso("W9XYZ"), false)
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_matching_dry_run_and_chunks() {
+        let transport = MemoryTransport::with_responses([
+            "RESULT=OK&COUNT=3&LOGIDS=11%2C12%2C13".to_string(),
+            "RESULT=OK&COUNT=3&LOGIDS=11%2C12%2C13".to_string(),
+            "RESULT=OK&COUNT=2".to_string(),
+            "RESULT=PARTIAL&COUNT=0&LOGIDS=13".to_string(),
+            "RESULT=OK&COUNT=3&LOGIDS=11%2C12%2C13".to_string(),
+            "RESULT=OK&COUNT=1".to_string(),
+            "RESULT=FAIL&REASON=server%20busy".to_string(),
+            "RESULT=OK&COUNT=1".to_string(),
+        ]);
+        let client = client_with(&transport);
+        let options = FetchOptions::new().call("N0CALL");
+
+        let preview = client
+            .delete_matching(&options, &DeleteOptions::dry_run())
+            .await
+            .unwrap();
+        assert_eq!(preview.logids, vec![11, 12, 13]);
+        assert_eq!(preview.deleted_count, 0);
+
+        let report = client
+            .delete_matching(&options, &DeleteOptions::new().chunk_size(2))
+            .await
+            .unwrap();
+        assert_eq!(report.deleted_count, 2);
+        assert_eq!(report.not_found_logids, vec![13]);
+        assert_eq!(report.confirmation_token, preview.confirmation_token);
+
+        assert!(report.failed_chunks.is_empty());
+
+        let requests = transport.requests();
+        assert_eq!(requests[2].param("LOGIDS"), Some("11,12"));
+        assert_eq!(requests[3].param("LOGIDS"), Some("13"));
+
+        let partial = client
+            .delete_matching(&options, &DeleteOptions::new().chunk_size(1))
+            .await
+            .unwrap();
+        assert_eq!(partial.deleted_count, 2);
+        assert_eq!(
+            partial.failed_chunks,
+            vec![(vec![12], "API error: server busy".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_metrics() {
+        let transport =
+            MemoryTransport::with_responses(["RESULT=OK&DATA=total_qsos%3D1", "RESULT=AUTH"]);
+        let metrics = Arc::new(RecordingMetrics::new());
+        let client = client_with(&transport).with_metrics(metrics.clone());
+
+        client.get_status().await.unwrap();
+        assert!(client.get_status().await.is_err());
+        assert!(client.fetch_qsos(&FetchOptions::all()).await.is_err());
+
+        let status_ok = [("action", "STATUS"), ("result", "OK")];
+        assert_eq!(metrics.counter(REQUESTS_TOTAL, &status_ok), 1);
+        assert_eq!(metrics.counter(REQUESTS_TOTAL, &[("result", "AUTH")]), 1);
+        assert_eq!(
+            metrics.counter(REQUESTS_TOTAL, &[("action", "FETCH"), ("result", "ERROR")]),
+            1
+        );
+
+        let latencies: Vec<_> = metrics
+            .observations()
+            .into_iter()
+            .filter(|o| o.name == REQUEST_DURATION_SECONDS)
+            .collect();
+        assert_eq!(latencies.len(), 3);
+        assert!(latencies.iter().all(|o| o.value.unwrap() >= 0.0));
+    }
+
+    struct Staging {
+        name: &'static str,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Staging {
+        fn before_send(&self, request: &mut TransportRequest) -> QrzLogbookResult<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {}", self.name));
+            if request.action() == Some("DELETE") {
+                return Err(QrzLogbookError::api_error("deletes disabled in staging"));
+            }
+            request.set_param("OPTION", "ALL");
+            Ok(())
+        }
+
+        fn after_receive(
+            &self,
+            _request: &TransportRequest,
+            response: &mut String,
+        ) -> QrzLogbookResult<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.name));
+            *response = response.replace("total_qsos%3D1", "total_qsos%3D2");
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_chain() {
+        let transport = MemoryTransport::with_responses(["RESULT=OK&DATA=total_qsos%3D1"]);
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = ResponseRecorder::new();
+        let client = client_with(&transport)
+            .with_middleware(Header::new("X-Proxy-Token", "abc"))
+            .with_middleware(recorder.clone())
+            .with_middleware(Staging {
+                name: "staging",
+                log: log.clone(),
+            });
+
+        let status = client.get_status().await.unwrap();
+        assert_eq!(status.data["total_qsos"], "2");
+
+        let request = &transport.requests()[0];
+        assert_eq!(request.header("x-proxy-token"), Some("abc"));
+        assert_eq!(option(request), "ALL");
+
+        // The recorder runs after the inner staging middleware rewrote the body
+        assert_eq!(
+            recorder.responses(),
+            vec![(
+                "STATUS".to_string(),
+                "RESULT=OK&DATA=total_qsos%3D2".to_string()
+            )]
+        );
+
+        // Faults injected before sending never reach the transport
+        assert!(client.delete_qsos(vec![1]).await.is_err());
+        assert_eq!(transport.requests().len(), 1);
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["before staging", "after staging", "before staging"]
+        );
+    }
+}
+
+#[cfg(feature = "testing")]
+mod fake_server_tests {
+    use super::*;
+    use qrz_logbook_api::{
+        backup::Backup,
+        middleware::Header,
+        testing::{FakeFailure, FakeLogbook, FakeQrzServer},
+    };
+
+    const KEY: &str = "ABCD-1234-EF56-7890";
+
+    fn qso(call: &str, band: &str) -> QsoRecord {
+        QsoRecord::builder()
+            .call(call)
+            .station_callsign("K1ABC")
+            .date(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap())
+            .time_on(NaiveTime::from_hms_opt(14, 30, 0).unwrap())
+            .band(band)
+            .mode("SSB")
+            .build()
+    }
+
+    async fn start() -> (FakeQrzServer, QrzLogbookClient) {
+        let server = FakeQrzServer::start(FakeLogbook::new("K1ABC", KEY)).await;
+        let client = server.client(KEY, "TestSuite/1.0.0 (K1ABC)").unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_insert_fetch_delete_over_http() {
+        let (server, client) = start().await;
+
+        let first = client.insert_qso(&qso("W1AW", "20m"), false).await.unwrap();
+        client
+            .insert_qso(&qso("DL1AB", "40m"), false)
+            .await
+            .unwrap();
+        assert!(client.insert_qso(&qso("W1AW", "20m"), false).await.is_err());
+
+        let replaced = client.insert_qso(&qso("W1AW", "20m"), true).await.unwrap();
+        assert_eq!(replaced.logid, first.logid);
+        assert_eq!(server.logbook().len(), 2);
+
+        let twenty = client
+            .fetch_all_qsos(&FetchOptions::new().band("20m"))
+            .await
+            .unwrap();
+        assert_eq!(twenty.len(), 1);
+        assert_eq!(twenty[0].call, "W1AW");
+
+        let status = client.get_status().await.unwrap();
+        assert_eq!(status.data["total_qsos"], "2");
+        assert_eq!(status.data["callsign"], "K1ABC");
+
+        let deleted = client.delete_qsos(vec![first.logid, 1]).await.unwrap();
+        assert_eq!(deleted.deleted_count, 1);
+        assert_eq!(deleted.not_found_logids, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_update_qso_over_http() {
+        let (server, client) = start().await;
+        let logid = client
+            .insert_qso(&qso("W1AW", "20m"), false)
+            .await
+            .unwrap()
+            .logid;
+
+        let result = client
+            .update_qso(logid, &QsoPatch::new().set("name", "Hiram"))
+            .await
+            .unwrap();
+        assert!(result.replaced);
+        assert_eq!(result.logid, logid);
+        assert_eq!(server.logbook().len(), 1);
+        assert_eq!(
+            server.logbook().get(logid).unwrap().qso.name.as_deref(),
+            Some("Hiram")
+        );
+
+        let moved = client
+            .update_qso(logid, &QsoPatch::new().set("band", "40m"))
+            .await
+            .unwrap();
+        assert!(!moved.replaced);
+        assert_ne!(moved.logid, logid);
+        assert_eq!(moved.previous_logid, logid);
+        assert!(moved.delete_error.is_none());
+        assert_eq!(server.logbook().len(), 1);
+        assert!(server.logbook().get(logid).is_none());
+
+        // Moving onto another record's band, call, date, time and mode fails
+        // without touching either record
+        let other = client
+            .insert_qso(&qso("W1AW", "20m"), false)
+            .await
+            .unwrap()
+            .logid;
+        assert!(client
+            .update_qso(moved.logid, &QsoPatch::new().set("band", "20m"))
+            .await
+            .is_err());
+        assert_eq!(server.logbook().len(), 2);
+        assert_eq!(server.logbook().get(other).unwrap().qso.name, None);
+        assert_eq!(server.logbook().get(moved.logid).unwrap().qso.band, "40m");
+
+        assert!(client.update_qso(424242, &QsoPatch::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_matching_confirmation_and_chunks() {
+        let (server, client) = start().await;
+        for i in 0..30 {
+            server.logbook().seed(qso(&format!("W{:05}", i), "20m"));
+        }
+        for i in 0..5 {
+            server.logbook().seed(qso(&format!("K{:05}", i), "40m"));
+        }
+        let twenty = FetchOptions::new().band("20m");
+
+        let preview = client
+            .delete_matching(&twenty, &DeleteOptions::dry_run())
+            .await
+            .unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.logids.len(), 30);
+        assert_eq!(server.logbook().len(), 35);
+
+        let unconfirmed = client.delete_matching(&twenty, &DeleteOptions::new()).await;
+        assert!(matches!(
+            unconfirmed,
+            Err(QrzLogbookError::ConfirmationRequired { count: 30, .. })
+        ));
+        let wrong_token = DeleteOptions::new().confirm("DELETE-30-0000000000000000");
+        assert!(client.delete_matching(&twenty, &wrong_token).await.is_err());
+
+        let requests_before = server.logbook().requests().len();
+        let confirmed = DeleteOptions::new()
+            .chunk_size(7)
+            .confirm(preview.confirmation_token);
+        let report = client.delete_matching(&twenty, &confirmed).await.unwrap();
+        assert_eq!(report.deleted_count, 30);
+        assert!(report.not_found_logids.is_empty());
+
+        let deletes: Vec<_> = server.logbook().requests()[requests_before..]
+            .iter()
+            .filter(|r| r.action() == Some("DELETE"))
+            .map(|r| r.param("LOGIDS").unwrap().split(',').count())
+            .collect();
+        assert_eq!(deletes, vec![7, 7, 7, 7, 2]);
+
+        // Small deletions below the threshold need no token
+        let forty = FetchOptions::new().band("40m");
+        let report = client
+            .delete_matching(&forty, &DeleteOptions::new())
+            .await
+            .unwrap();
+        assert_eq!(report.deleted_count, 5);
+        assert!(server.logbook().is_empty());
+
+        assert!(client
+            .delete_matching(&forty, &DeleteOptions::new().chunk_size(0))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let (server, client) = start().await;
+        let first = server.logbook().seed(qso("W1AW", "20m"));
+        server.logbook().seed(qso("DL1AB", "40m"));
+        server.logbook().seed(qso("JA1XY", "15m"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logbook.tar.gz");
+        client.backup().await.unwrap().save(&path).unwrap();
+
+        client.delete_qsos(vec![first]).await.unwrap();
+        client
+            .insert_qso(&qso("VK2AB", "10m"), false)
+            .await
+            .unwrap();
+
+        let backup = Backup::load(&path).unwrap();
+        assert_eq!(backup.manifest.qso_count, 3);
+        assert_eq!(backup.manifest.logids.len(), 3);
+        assert_eq!(backup.manifest.status["total_qsos"], "3");
+
+        let preview = client.restore(&backup, true).await.unwrap();
+        assert_eq!(preview.missing.len(), 1);
+        assert_eq!(preview.missing[0].call, "W1AW");
+        assert_eq!(preview.unchanged, 2);
+        assert_eq!(preview.added_since_backup[0].call, "VK2AB");
+        assert!(preview.restored.is_empty());
+        assert_eq!(server.logbook().len(), 3);
+
+        let report = client.restore(&backup, false).await.unwrap();
+        assert_eq!(report.restored.len(), 1);
+        assert!(report.failed.is_empty());
+        assert_eq!(server.logbook().len(), 4);
+
+        let (restored, logid) = &report.restored[0];
+        assert_ne!(*logid, first);
+        assert!(!restored.additional_fields.contains_key("app_qrzlog_logid"));
+    }
+
+    #[tokio::test]
+    async fn test_paging_over_http() {
+        let (server, client) = start().await;
+        for i in 0..260 {
+            server.logbook().seed(qso(&format!("W{:05}", i), "20m"));
+        }
+
+        let qsos = client.fetch_all_qsos(&FetchOptions::all()).await.unwrap();
+        assert_eq!(qsos.len(), 260);
+        assert_eq!(
+            client
+                .fetch_logids(&FetchOptions::all())
+                .await
+                .unwrap()
+                .len(),
+            260
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_and_injected_failures_over_http() {
+        let (server, client) = start().await;
+
+        let wrong_key = server
+            .client("9999-9999-9999-9999", "TestSuite/1.0.0 (K1ABC)")
+            .unwrap();
+        assert!(matches!(
+            wrong_key.verify_key().await,
+            Err(QrzLogbookError::KeyRejected)
+        ));
+
+        server.logbook().add_unprivileged_key("9999-9999-9999-9999");
+        assert!(matches!(
+            wrong_key.verify_key().await,
+            Err(QrzLogbookError::Auth)
+        ));
+
+        let info = client.verify_key().await.unwrap();
+        assert_eq!(info.callsign, "K1ABC");
+        assert_eq!(info.book_id.as_deref(), Some("12345"));
+
+        let with_header = server
+            .client(KEY, "TestSuite/1.0.0 (K1ABC)")
+            .unwrap()
+            .with_middleware(Header::new("X-Proxy-Token", "abc"));
+        with_header.get_status().await.unwrap();
+        let requests = server.logbook().requests();
+        assert_eq!(
+            requests.last().unwrap().header("x-proxy-token"),
+            Some("abc")
+        );
+
+        server.logbook().fail_next(FakeFailure::HttpStatus(500));
+        assert!(matches!(
+            client.get_status().await,
+            Err(QrzLogbookError::Http(_))
+        ));
+
+        server
+            .logbook()
+            .set_latency(Some(std::time::Duration::from_millis(50)));
+        let started = std::time::Instant::now();
+        client.get_status().await.unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(50));
+    }
+}